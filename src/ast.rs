@@ -0,0 +1,33 @@
+//! Syntax tree of parsed markdown.
+//!
+//! Use [`crate::parse`] to get [`Document`] from markdown text.
+//!
+//! ```
+//! use flav_md_engine::ast::{Content, ElementNode, Token};
+//!
+//! let document = flav_md_engine::parse("# hello\nworld");
+//! let headings: Vec<&Content> = document
+//!     .element_nodes()
+//!     .iter()
+//!     .filter_map(|node| match node {
+//!         ElementNode::Exist { tag, content, .. } if tag.is_head() => Some(&**content),
+//!         _ => None,
+//!     })
+//!     .collect();
+//! assert_eq!(headings.len(), 1);
+//! ```
+//!
+//! Every [`ElementNode`] and [`InlineNode`] has [`Span`] pointing to its source text,
+//! with 1-based line and column and 0-based byte offset.
+pub use crate::lexer::builder::builder::MdNode;
+pub use crate::lexer::lexer::lexer::{
+    Align, Content, ElementNode, InlineNode, Table, TableHead, TableRow, Token,
+};
+pub use crate::lexer::source::source::{Position, Span};
+pub use crate::lexer::visitor::visitor::{
+    walk_document, walk_document_mut, walk_element_node, walk_element_node_mut, walk_inline_node,
+    walk_inline_node_mut, Visitor, VisitorMut,
+};
+
+/// Root of the syntax tree.
+pub type Document = MdNode;
@@ -1,3 +1,12 @@
 pub mod builder;
+pub mod code_handler;
+pub mod error;
+#[cfg(feature = "highlight")]
+pub mod highlight;
 pub mod lexer;
 pub mod pattern;
+pub mod renderer;
+pub mod sanitizer;
+pub mod source;
+pub mod url_policy;
+pub mod visitor;
@@ -1,245 +1,80 @@
 pub mod builder {
-    use crate::lexer::lexer::lexer::Table;
-    use crate::lexer::lexer::lexer::{Content, ElementNode, TableHead, Token};
-    use std::borrow::Borrow;
+    use crate::lexer::error::error::ParseError;
+    use crate::lexer::lexer::lexer::ElementNode;
+    use crate::lexer::renderer::renderer::{FlavHtmlRenderer, RenderOptions, Renderer};
+    use crate::lexer::visitor::visitor::{walk_document, walk_document_mut, Visitor, VisitorMut};
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
 
-    fn generate_class_for_the_tag(tag: &Token) -> Vec<String> {
-        let mut tags = vec!["flav-md-text".to_string()];
-        match tag {
-            Token::H1 | Token::H2 | Token::H3 | Token::H4 | Token::H5 | Token::H6 => {
-                tags.push(format!("flav-md-{}", tag.value()));
-                tags.push("flav-md-h".to_string());
-            }
-            Token::P => {
-                tags.push("flav-md-p".to_string());
+    #[derive(Debug, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct MdNode {
+        element_nodes: Vec<ElementNode>,
+        diagnostics: Vec<ParseError>,
+    }
+
+    impl MdNode {
+        pub fn new(element_nodes: Vec<ElementNode>) -> Self {
+            MdNode {
+                element_nodes,
+                diagnostics: vec![],
             }
-            Token::Blockquote => {
-                tags.push("flav-md-blockquote".to_string());
+        }
+
+        pub fn with_diagnostics(self, diagnostics: Vec<ParseError>) -> Self {
+            MdNode {
+                diagnostics,
+                ..self
             }
-            _ => {}
         }
-        tags
-    }
 
-    fn create_thead(head: &Vec<TableHead>) -> String {
-        let heads = head
-            .iter()
-            .map(|item| {
-                format!(
-                    "      <th style=\"text-align: {}\">{}</th>",
-                    item.get_align(),
-                    item.cell
-                )
-                .to_string()
-            })
-            .collect::<Vec<String>>()
-            .join("\n");
-        format!(
-            r#"  <thead>
-    <tr>
-{}
-    </tr>
-  </thead>"#,
-            heads
-        )
-        .to_string()
-    }
+        /// Top level nodes of the document.
+        pub fn element_nodes(&self) -> &[ElementNode] {
+            &self.element_nodes
+        }
 
-    fn create_tbody(head: &Vec<TableHead>, body: &Vec<Vec<String>>) -> String {
-        let trs = body
-            .iter()
-            .map(|rows| {
-                let tds = rows
-                    .iter()
-                    .enumerate()
-                    .map(|(i, item)| {
-                        format!(
-                            "      <td style=\"text-align: {}\">{}</td>",
-                            head.get(i).unwrap().get_align(),
-                            item
-                        )
-                        .to_string()
-                    })
-                    .collect::<Vec<String>>()
-                    .join("\n");
-                format!(
-                    r#"    <tr>
-{}
-    </tr>"#,
-                    tds
-                )
-                .to_string()
-            })
-            .collect::<Vec<String>>()
-            .join("\n");
-        format!(
-            r#"  <tbody>
-{}
-  </tbody>"#,
-            trs
-        )
-        .to_string()
-    }
+        pub fn element_nodes_mut(&mut self) -> &mut Vec<ElementNode> {
+            &mut self.element_nodes
+        }
 
-    fn generate_table(content: &Table) -> String {
-        let head = &content.head;
-        let body = &content.body;
-        let thead = create_thead(&head);
-        let tbody = create_tbody(&head, body);
-        format!(
-            r#"<table>
-{}
-{}
-</table>"#,
-            thead, tbody
-        )
-    }
+        /// Walk the whole document with `visitor`.
+        pub fn walk<V: Visitor>(&self, visitor: &mut V) {
+            walk_document(visitor, self);
+        }
 
-    fn parse_nested_tag(items: &Vec<ElementNode>, indent: usize) -> String {
-        let mut results = "".to_string();
-        for item in items.into_iter() {
-            results += &format!("{}\n", create_tag(&item, indent));
+        /// Walk the whole document with `visitor`, which can rewrite the nodes.
+        pub fn walk_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+            walk_document_mut(visitor, self);
         }
-        results
-    }
 
-    fn create_tag(item: &ElementNode, indent: usize) -> String {
-        match item {
-            ElementNode::Exist {
-                tag,
-                content,
-                children,
-            } => {
-                let classes = generate_class_for_the_tag(&tag);
-                let whiltespace = " ".repeat(indent);
-                match tag {
-                    Token::Ul => match &**content {
-                        Content::ElementNodes { value } => {
-                            let content = parse_nested_tag(value.borrow(), indent + 2);
-                            format!(
-                                r#"<ul class="flav-md-ul">
-{}{}</ul>"#,
-                                content,
-                                " ".repeat(indent)
-                            )
-                        }
-                        _ => "".to_string(),
-                    },
-                    Token::Ol => match &**content {
-                        Content::ElementNodes { value } => {
-                            let content = parse_nested_tag(&value, indent + 2);
-                            format!(
-                                r#"<ol class="flav-md-ol">
-{}{}</ol>"#,
-                                content,
-                                " ".repeat(indent)
-                            )
-                        }
-                        _ => "".to_string(),
-                    },
-                    Token::Li => {
-                        let content = match &**content {
-                            Content::PlainText { value } => value,
-                            _ => "",
-                        };
-                        match **children {
-                            ElementNode::Exist { .. } => {
-                                let c = format!(
-                                    "{}{}",
-                                    " ".repeat(indent + 2),
-                                    create_tag(&*children, indent + 2)
-                                );
-                                format!(
-                                    r#"{}<li class="flav-md-text flav-md-li">{}
-{}
-{}</li>"#,
-                                    whiltespace, content, c, whiltespace
-                                )
-                            }
-                            _ => {
-                                format!(
-                                    "{}<li class=\"flav-md-text flav-md-li\">{}</li>",
-                                    " ".repeat(indent),
-                                    content,
-                                )
-                            }
-                        }
-                    }
-                    Token::Blockquote => match &**content {
-                        Content::ElementNodes { value } => {
-                            format!(
-                                r#"{}<blockquote class="{}">
-{}{}</blockquote>"#,
-                                whiltespace,
-                                classes.join(" "),
-                                parse_nested_tag(&value, indent + 2),
-                                whiltespace
-                            )
-                        }
-                        _ => "".to_string(),
-                    },
-                    Token::Code => match &**content {
-                        Content::PlainText { value } => {
-                            format!(
-                                r#"<code class="flav-md-code">
-  {}
-</code>"#,
-                                value
-                            )
-                        }
-                        _ => "".to_string(),
-                    },
-                    Token::Table => match &**content {
-                        Content::Table { value } => generate_table(&value),
-                        _ => "".to_string(),
-                    },
-                    _ => {
-                        let content = match &**content {
-                            Content::PlainText { value } => value,
-                            _ => "",
-                        };
-                        let tag = tag.value();
-                        format!(
-                            r#"{}<{} class="{}">{}</{}>"#,
-                            whiltespace,
-                            tag,
-                            classes.join(" "),
-                            content,
-                            tag
-                        )
-                    }
-                }
-            }
-            _ => "".to_string(),
+        /// Errors recovered while parsing.
+        pub fn diagnostics(&self) -> &[ParseError] {
+            &self.diagnostics
         }
-        .to_string()
-    }
 
-    #[derive(Debug, PartialEq)]
-    pub struct MdNode {
-        element_nodes: Vec<ElementNode>,
-    }
+        pub fn to_html_string(&self) -> String {
+            self.render(&FlavHtmlRenderer::default())
+        }
 
-    impl MdNode {
-        pub fn new(element_nodes: Vec<ElementNode>) -> Self {
-            MdNode { element_nodes }
+        /// Same as [`MdNode::to_html_string`], but rendered with `options`.
+        pub fn to_html_string_with(&self, options: &RenderOptions) -> String {
+            self.render(&FlavHtmlRenderer::new(options.clone()))
         }
 
-        pub fn to_html_string(&self) -> String {
-            self.element_nodes
-                .iter()
-                .map(|i| create_tag(&i, 0))
-                .collect::<Vec<String>>()
-                .join("\n")
+        /// Render the whole document with `renderer`.
+        pub fn render<R: Renderer + ?Sized>(&self, renderer: &R) -> String {
+            renderer.document(self)
         }
     }
 
     #[cfg(test)]
     mod test_builder {
         use super::*;
-        use crate::lexer::lexer::lexer::{Align, Content, Table, TableHead, Token};
-        use crate::vec_string;
-        use crate::{content_element_nodes, content_plain_text, element_node, table};
+        use crate::lexer::lexer::lexer::{Align, Content, InlineNode, Table, TableHead, Token};
+        use crate::{
+            content_code_block, content_element_nodes, content_inline_nodes, content_list,
+            content_list_item, element_node, inline_text, table,
+        };
         use pretty_assertions::assert_eq;
 
         #[test]
@@ -284,9 +119,9 @@ pub mod builder {
   </blockquote>
 </blockquote>
 <h2 class="flav-md-text flav-md-h2 flav-md-h">world</h2>
-<code class="flav-md-code">
-  &lt;script src=&quot;hoge.js&quot;&gt;&lt;/script&gt;<br />&lt;script src=&quot;hoge.js&quot;&gt;&lt;/script&gt;
-</code>
+<pre><code class="flav-md-code">&lt;script src=&quot;hoge.js&quot;&gt;&lt;/script&gt;
+&lt;script src=&quot;hoge.js&quot;&gt;&lt;/script&gt;
+</code></pre>
 <p class="flav-md-text flav-md-p">this is <code class="flav-md-code-inline">hoge</code> and <code class="flav-md-code-inline">fuga</code></p>
 <p class="flav-md-text flav-md-p">this is <em class="flav-md-em">hoge</em></p>
 <p class="flav-md-text flav-md-p">this is <strong class="flav-md-strong">hoge</strong></p>
@@ -316,115 +151,195 @@ pub mod builder {
             let md_node = MdNode::new(vec![
                 element_node! {
                     tag: Token::H1,
-                    content: content_plain_text!("hello".to_string()),
+                    content: content_inline_nodes![inline_text!("hello")],
                 },
                 element_node! {
                     tag: Token::H2,
-                    content: content_plain_text!("world".to_string()),
+                    content: content_inline_nodes![inline_text!("world")],
                 },
                 element_node! {
                     tag: Token::Ul,
-                    content: content_element_nodes![
+                    content: content_list![
                         element_node! {
                             tag: Token::Li,
-                            content: content_plain_text!("hogehoge".to_string()),
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("hogehoge")],
+                                },
+                            ],
                         },
                         element_node! {
                             tag: Token::Li,
-                            content: content_plain_text!("hogehoge1".to_string()),
-                            children: element_node! {
-                                tag: Token::Ul,
-                                content: content_element_nodes![
-                                    element_node! {
-                                        tag: Token::Li,
-                                        content: content_plain_text!(r#"this is <a class="flav-md-a" href="https://example.com" alt="Google先生">Google先生</a>"#.to_string()),
-                                    },
-                                    element_node! {
-                                        tag: Token::Li,
-                                        content: content_plain_text!("hogehoge3".to_string()),
-                                        children: element_node! {
-                                            tag: Token::Ul,
-                                            content: content_element_nodes![
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("hogehoge1")],
+                                },
+                                element_node! {
+                                    tag: Token::Ul,
+                                    content: content_list![
+                                        element_node! {
+                                            tag: Token::Li,
+                                            content: content_list_item![
+                                                element_node! {
+                                                    tag: Token::P,
+                                                    content: content_inline_nodes![inline_text!("this is "),
+                                    InlineNode::Link {
+                                        href: "https://example.com".to_string(),
+                                        title: None,
+                                        children: vec![inline_text!("Google先生")],
+                                        span: Default::default(),
+                                    }],
+                                                },
+                                            ],
+                                        },
+                                        element_node! {
+                                            tag: Token::Li,
+                                            content: content_list_item![
                                                 element_node! {
-                                                    tag: Token::Li,
-                                                    content: content_plain_text!("hoge 4".to_string()),
-                                                }
+                                                    tag: Token::P,
+                                                    content: content_inline_nodes![inline_text!("hogehoge3")],
+                                                },
+                                                element_node! {
+                                                    tag: Token::Ul,
+                                                    content: content_list![
+                                                        element_node! {
+                                                            tag: Token::Li,
+                                                            content: content_list_item![
+                                                                element_node! {
+                                                                    tag: Token::P,
+                                                                    content: content_inline_nodes![inline_text!("hoge 4")],
+                                                                },
+                                                            ],
+                                                        }
+                                                    ]
+                                                },
                                             ]
-                                        }
-                                    },
-                                ],
-                            }
+                                        },
+                                    ],
+                                },
+                            ]
                         },
                         element_node! {
                             tag: Token::Li,
-                            content: content_plain_text!("hogehoge4".to_string()),
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("hogehoge4")],
+                                },
+                            ],
                         },
                     ]
                 },
                 element_node! {
                     tag: Token::Ol,
-                    content: content_element_nodes![
+                    content: content_list![
+                        start: 1,
                         element_node! {
                             tag: Token::Li,
-                            content: content_plain_text!("hoge1".to_string()),
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("hoge1")],
+                                },
+                            ],
                         },
                         element_node! {
                             tag: Token::Li,
-                            content: content_plain_text!("hoge2".to_string()),
-                            children: element_node! {
-                                tag: Token::Ol,
-                                content: content_element_nodes![
-                                    element_node! {
-                                        tag: Token::Li,
-                                        content: content_plain_text!("aaa".to_string()),
-                                    },
-                                    element_node! {
-                                        tag: Token::Li,
-                                        content: content_plain_text!("ccc".to_string()),
-                                        children: element_node! {
-                                            tag: Token::Ol,
-                                            content: content_element_nodes![
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("hoge2")],
+                                },
+                                element_node! {
+                                    tag: Token::Ol,
+                                    content: content_list![
+                                        start: 1,
+                                        element_node! {
+                                            tag: Token::Li,
+                                            content: content_list_item![
+                                                element_node! {
+                                                    tag: Token::P,
+                                                    content: content_inline_nodes![inline_text!("aaa")],
+                                                },
+                                            ],
+                                        },
+                                        element_node! {
+                                            tag: Token::Li,
+                                            content: content_list_item![
                                                 element_node! {
-                                                    tag: Token::Li,
-                                                    content: content_plain_text!("ddd".to_string()),
-                                                }
+                                                    tag: Token::P,
+                                                    content: content_inline_nodes![inline_text!("ccc")],
+                                                },
+                                                element_node! {
+                                                    tag: Token::Ol,
+                                                    content: content_list![
+                                                        start: 1,
+                                                        element_node! {
+                                                            tag: Token::Li,
+                                                            content: content_list_item![
+                                                                element_node! {
+                                                                    tag: Token::P,
+                                                                    content: content_inline_nodes![inline_text!("ddd")],
+                                                                },
+                                                            ],
+                                                        }
+                                                    ]
+                                                },
                                             ]
-                                        }
-                                    },
-                                ],
-                            }
+                                        },
+                                    ],
+                                },
+                            ]
                         },
                     ]
                 },
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!(r#"this is <a class="flav-md-a" href="https://example.com" alt="Google先生">Google先生</a>"#.to_string()),
+                    content: content_inline_nodes![
+                        inline_text!("this is "),
+                        InlineNode::Link {
+                            href: "https://example.com".to_string(),
+                            title: None,
+                            children: vec![inline_text!("Google先生")],
+                            span: Default::default(),
+                        },
+                    ],
                 },
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!(r#"画像 <img class="flav-md-img" src="https://example.com" alt="エビフライトライアングル">"#.to_string()),
+                    content: content_inline_nodes![
+                        inline_text!("画像 "),
+                        InlineNode::Image {
+                            src: "https://example.com".to_string(),
+                            alt: "エビフライトライアングル".to_string(),
+                            title: None,
+                            span: Default::default(),
+                        },
+                    ],
                 },
                 element_node! {
                     tag: Token::Blockquote,
                     content: content_element_nodes![
                         element_node! {
                             tag: Token::P,
-                            content: content_plain_text!("aaa".to_string()),
+                            content: content_inline_nodes![inline_text!("aaa")],
                         },
                         element_node! {
                             tag: Token::P,
-                            content: content_plain_text!("bbb".to_string()),
+                            content: content_inline_nodes![inline_text!("bbb")],
                         },
                         element_node! {
                             tag: Token::Blockquote,
                             content: content_element_nodes![
                                 element_node! {
                                     tag: Token::P,
-                                    content: content_plain_text!("ccc".to_string()),
+                                    content: content_inline_nodes![inline_text!("ccc")],
                                 },
                                 element_node! {
                                     tag: Token::P,
-                                    content: content_plain_text!("ddd".to_string()),
+                                    content: content_inline_nodes![inline_text!("ddd")],
                                 },
                             ],
                         },
@@ -432,47 +347,93 @@ pub mod builder {
                 },
                 element_node! {
                     tag: Token::H2,
-                    content: content_plain_text!("world".to_string()),
+                    content: content_inline_nodes![inline_text!("world")],
                 },
                 element_node! {
                     tag: Token::Code,
-                    content: content_plain_text!("&lt;script src=&quot;hoge.js&quot;&gt;&lt;/script&gt;<br />&lt;script src=&quot;hoge.js&quot;&gt;&lt;/script&gt;".to_string()),
+                    content: content_code_block!(
+                        "<script src=\"hoge.js\"></script>\n<script src=\"hoge.js\"></script>"
+                            .to_string()
+                    ),
                 },
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!(r#"this is <code class="flav-md-code-inline">hoge</code> and <code class="flav-md-code-inline">fuga</code>"#.to_string()),
+                    content: content_inline_nodes![
+                        inline_text!("this is "),
+                        InlineNode::Code {
+                            value: "hoge".to_string(),
+                            span: Default::default(),
+                        },
+                        inline_text!(" and "),
+                        InlineNode::Code {
+                            value: "fuga".to_string(),
+                            span: Default::default(),
+                        },
+                    ],
                 },
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!(r#"this is <em class="flav-md-em">hoge</em>"#.to_string()),
+                    content: content_inline_nodes![
+                        inline_text!("this is "),
+                        InlineNode::Emphasis {
+                            children: vec![inline_text!("hoge")],
+                            span: Default::default(),
+                        },
+                    ],
                 },
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!(r#"this is <strong class="flav-md-strong">hoge</strong>"#.to_string()),
+                    content: content_inline_nodes![
+                        inline_text!("this is "),
+                        InlineNode::Strong {
+                            children: vec![inline_text!("hoge")],
+                            span: Default::default(),
+                        },
+                    ],
                 },
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!(r#"this is <em class="flav-md-em">hoge <strong class="flav-md-strong">fuga</strong></em>"#.to_string()),
+                    content: content_inline_nodes![
+                        inline_text!("this is "),
+                        InlineNode::Emphasis {
+                            children: vec![
+                                inline_text!("hoge "),
+                                InlineNode::Strong {
+                                    children: vec![inline_text!("fuga")],
+                                    span: Default::default(),
+                                },
+                            ],
+                            span: Default::default(),
+                        },
+                    ],
                 },
                 element_node! {
                     tag: Token::Table,
                     content: Content::Table {
                         value: table! {
                             head: vec![
-                                TableHead::new("head1".to_string(), Align::Center),
-                                TableHead::new("head2".to_string(), Align::Right),
-                                TableHead::new("head3".to_string(), Align::Left),
+                                TableHead::new(vec![inline_text!("head1")], Align::Center),
+                                TableHead::new(vec![inline_text!("head2")], Align::Right),
+                                TableHead::new(vec![inline_text!("head3")], Align::Left),
                             ],
                             body: vec![
-                                vec_string!["aaa1", "bbb1", "ccc1"],
-                                vec_string!["aaa2", "bbb2", "ccc2"],
+                                vec![
+                                    vec![inline_text!("aaa1")],
+                                    vec![inline_text!("bbb1")],
+                                    vec![inline_text!("ccc1")],
+                                ],
+                                vec![
+                                    vec![inline_text!("aaa2")],
+                                    vec![inline_text!("bbb2")],
+                                    vec![inline_text!("ccc2")],
+                                ],
                             ],
                         },
                     }
                 },
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!("aaa".to_string()),
+                    content: content_inline_nodes![inline_text!("aaa")],
                 },
             ]);
             let actual = md_node.to_html_string();
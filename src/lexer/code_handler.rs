@@ -0,0 +1,111 @@
+pub mod code_handler {
+    use crate::lexer::lexer::lexer::ElementNode;
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::Arc;
+
+    /// Replacement of a fenced code block returned by [`CodeBlockHandler`].
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum CodeBlockOutput {
+        /// Html emitted as it is, so it must be safe for the document.
+        Html(String),
+        /// Nodes rendered in place of the code block.
+        ElementNodes(Vec<ElementNode>),
+    }
+
+    /// Handler given the code and the `meta` of its info string.
+    ///
+    /// Returning `None` renders the code block as usual.
+    pub type CodeBlockHandler =
+        Arc<dyn Fn(&str, Option<&str>) -> Option<CodeBlockOutput> + Send + Sync>;
+
+    /// Handlers of fenced code blocks per language, compared ignoring case.
+    #[derive(Clone, Default)]
+    pub struct CodeBlockHandlers {
+        handlers: HashMap<String, CodeBlockHandler>,
+    }
+
+    impl CodeBlockHandlers {
+        /// Handle code blocks of `language` with `handler`, replacing the previous one.
+        pub fn insert<F>(&mut self, language: &str, handler: F)
+        where
+            F: Fn(&str, Option<&str>) -> Option<CodeBlockOutput> + Send + Sync + 'static,
+        {
+            self.handlers
+                .insert(language.to_ascii_lowercase(), Arc::new(handler));
+        }
+
+        /// Remove the handler of `language`.
+        pub fn remove(&mut self, language: &str) {
+            self.handlers.remove(&language.to_ascii_lowercase());
+        }
+
+        /// Run the handler of `language`, or return `None` if there is no handler.
+        pub fn handle(
+            &self,
+            language: &str,
+            code: &str,
+            meta: Option<&str>,
+        ) -> Option<CodeBlockOutput> {
+            self.handlers
+                .get(&language.to_ascii_lowercase())
+                .and_then(|handler| handler(code, meta))
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.handlers.is_empty()
+        }
+    }
+
+    impl fmt::Debug for CodeBlockHandlers {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let mut languages: Vec<&String> = self.handlers.keys().collect();
+            languages.sort();
+            f.debug_struct("CodeBlockHandlers")
+                .field("languages", &languages)
+                .finish()
+        }
+    }
+
+    /// Handlers are equal when they are the same closures for the same languages.
+    impl PartialEq for CodeBlockHandlers {
+        fn eq(&self, other: &Self) -> bool {
+            self.handlers.len() == other.handlers.len()
+                && self.handlers.iter().all(|(language, handler)| {
+                    other
+                        .handlers
+                        .get(language)
+                        .is_some_and(|other| Arc::ptr_eq(handler, other))
+                })
+        }
+    }
+
+    #[cfg(test)]
+    mod test_code_handler {
+        use super::*;
+
+        #[test]
+        fn test_handle() {
+            let mut handlers = CodeBlockHandlers::default();
+            handlers.insert("Mermaid", |code, meta| {
+                Some(CodeBlockOutput::Html(format!(
+                    r#"<div class="mermaid" title="{}">{}</div>"#,
+                    meta.unwrap_or(""),
+                    code
+                )))
+            });
+            handlers.insert("csv", |_, _| None);
+            assert_eq!(
+                handlers.handle("mermaid", "graph TD", Some("big")),
+                Some(CodeBlockOutput::Html(String::from(
+                    r#"<div class="mermaid" title="big">graph TD</div>"#
+                )))
+            );
+            assert_eq!(handlers.handle("csv", "a,b", None), None);
+            assert_eq!(handlers.handle("rust", "fn main() {}", None), None);
+            assert_eq!(handlers.clone(), handlers);
+            handlers.remove("MERMAID");
+            assert_eq!(handlers.handle("mermaid", "graph TD", None), None);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+pub mod error {
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+    use std::fmt;
+    use wasm_bindgen::JsValue;
+
+    /// Error reported when markdown cannot be parsed strictly.
+    ///
+    /// `line` and `column` are 1-based positions in the source text.
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum ParseError {
+        /// Code fence which is never closed.
+        UnterminatedCodeBlock { line: usize, column: usize },
+        /// Table whose delimiter row is missing, or whose rows do not match the head.
+        MalformedTable { line: usize, column: usize },
+        /// List item whose indent does not match any open list level.
+        InconsistentListIndent { line: usize, column: usize },
+    }
+
+    impl ParseError {
+        pub fn line(&self) -> usize {
+            match *self {
+                ParseError::UnterminatedCodeBlock { line, .. }
+                | ParseError::MalformedTable { line, .. }
+                | ParseError::InconsistentListIndent { line, .. } => line,
+            }
+        }
+
+        pub fn column(&self) -> usize {
+            match *self {
+                ParseError::UnterminatedCodeBlock { column, .. }
+                | ParseError::MalformedTable { column, .. }
+                | ParseError::InconsistentListIndent { column, .. } => column,
+            }
+        }
+
+        fn message(&self) -> &'static str {
+            match self {
+                ParseError::UnterminatedCodeBlock { .. } => "code block is not terminated",
+                ParseError::MalformedTable { .. } => "table is malformed",
+                ParseError::InconsistentListIndent { .. } => "list indent is inconsistent",
+            }
+        }
+    }
+
+    impl fmt::Display for ParseError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "{} at line {}, column {}",
+                self.message(),
+                self.line(),
+                self.column()
+            )
+        }
+    }
+
+    impl std::error::Error for ParseError {}
+
+    /// Collects errors found while parsing.
+    ///
+    /// In strict mode the first error aborts parsing. Otherwise the parser
+    /// recovers from it and the error is kept as a diagnostic.
+    #[derive(Debug)]
+    pub struct Diagnostics {
+        strict: bool,
+        errors: Vec<ParseError>,
+    }
+
+    impl Diagnostics {
+        pub fn strict() -> Self {
+            Diagnostics {
+                strict: true,
+                errors: vec![],
+            }
+        }
+
+        pub fn lenient() -> Self {
+            Diagnostics {
+                strict: false,
+                errors: vec![],
+            }
+        }
+
+        /// Report `error`, returning it back when parsing must be aborted.
+        pub fn report(&mut self, error: ParseError) -> Result<(), ParseError> {
+            if self.strict {
+                return Err(error);
+            }
+            self.errors.push(error);
+            Ok(())
+        }
+
+        pub fn into_errors(self) -> Vec<ParseError> {
+            self.errors
+        }
+    }
+
+    impl From<ParseError> for JsValue {
+        fn from(error: ParseError) -> Self {
+            JsValue::from_str(&error.to_string())
+        }
+    }
+
+    #[cfg(test)]
+    mod test_error {
+        use super::*;
+
+        #[test]
+        fn test_display() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: ParseError,
+                expected: String,
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should display unterminated code block"),
+                    input: ParseError::UnterminatedCodeBlock { line: 3, column: 1 },
+                    expected: String::from("code block is not terminated at line 3, column 1"),
+                },
+                TestCase {
+                    it: String::from("should display malformed table"),
+                    input: ParseError::MalformedTable { line: 2, column: 9 },
+                    expected: String::from("table is malformed at line 2, column 9"),
+                },
+                TestCase {
+                    it: String::from("should display inconsistent list indent"),
+                    input: ParseError::InconsistentListIndent { line: 4, column: 3 },
+                    expected: String::from("list indent is inconsistent at line 4, column 3"),
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let output = test_case.input.to_string();
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+
+        #[test]
+        fn test_report() {
+            let error = ParseError::MalformedTable { line: 2, column: 1 };
+
+            let mut strict = Diagnostics::strict();
+            assert_eq!(strict.report(error.clone()), Err(error.clone()));
+            assert_eq!(strict.into_errors(), vec![]);
+
+            let mut lenient = Diagnostics::lenient();
+            assert_eq!(lenient.report(error.clone()), Ok(()));
+            assert_eq!(lenient.into_errors(), vec![error]);
+        }
+    }
+}
@@ -0,0 +1,484 @@
+pub mod highlight {
+    use crate::util::string::string::escape_code_string;
+
+    /// Kind of highlighted token, used as `{prefix}-hl-{kind}` class.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum TokenKind {
+        Keyword,
+        String,
+        Comment,
+    }
+
+    impl TokenKind {
+        fn class_name(&self) -> &'static str {
+            match self {
+                TokenKind::Keyword => "hl-keyword",
+                TokenKind::String => "hl-string",
+                TokenKind::Comment => "hl-comment",
+            }
+        }
+    }
+
+    /// String literal from `open` to `close`.
+    struct Quote {
+        open: &'static str,
+        close: &'static str,
+        multiline: bool,
+    }
+
+    const fn quote(open: &'static str, close: &'static str, multiline: bool) -> Quote {
+        Quote {
+            open,
+            close,
+            multiline,
+        }
+    }
+
+    /// Lexical rules of a language, just enough to find keywords, strings and comments.
+    struct Syntax {
+        names: &'static [&'static str],
+        keywords: &'static [&'static str],
+        line_comments: &'static [&'static str],
+        block_comments: &'static [(&'static str, &'static str)],
+        quotes: &'static [Quote],
+        /// Line comment starts only at the head of a word, as `#` in shell.
+        comment_after_space: bool,
+        /// Markup, whose tag names are keywords and strings appear only in tags.
+        markup: bool,
+        /// `'` starts a char literal such as `'x'`, or a lifetime such as `'a` otherwise.
+        char_literals: bool,
+    }
+
+    const C_QUOTES: [Quote; 2] = [quote("\"", "\"", false), quote("'", "'", false)];
+
+    /// Keywords of javascript, which typescript also has.
+    const JS_KEYWORDS: &[&str] = &[
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "import",
+        "in",
+        "instanceof",
+        "let",
+        "new",
+        "null",
+        "of",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "yield",
+    ];
+
+    const TS_ONLY_KEYWORDS: &[&str] = &[
+        "as",
+        "get",
+        "enum",
+        "implements",
+        "interface",
+        "keyof",
+        "namespace",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "type",
+    ];
+
+    const TS_KEYWORDS: [&str; JS_KEYWORDS.len() + TS_ONLY_KEYWORDS.len()] =
+        concat_keywords(JS_KEYWORDS, TS_ONLY_KEYWORDS);
+
+    /// `head` followed by `tail`, whose lengths add up to `N`.
+    const fn concat_keywords<const N: usize>(
+        head: &[&'static str],
+        tail: &[&'static str],
+    ) -> [&'static str; N] {
+        let mut rslt = [""; N];
+        let mut i = 0;
+        while i < N {
+            rslt[i] = if i < head.len() {
+                head[i]
+            } else {
+                tail[i - head.len()]
+            };
+            i += 1;
+        }
+        rslt
+    }
+
+    const SYNTAXES: [Syntax; 9] = [
+        Syntax {
+            names: &["rust", "rs"],
+            keywords: &[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+                "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct",
+                "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+            ],
+            line_comments: &["//"],
+            block_comments: &[("/*", "*/")],
+            quotes: &[quote("\"", "\"", true)],
+            comment_after_space: false,
+            markup: false,
+            char_literals: true,
+        },
+        Syntax {
+            names: &["javascript", "js", "jsx", "mjs", "cjs"],
+            keywords: JS_KEYWORDS,
+            line_comments: &["//"],
+            block_comments: &[("/*", "*/")],
+            quotes: &[
+                quote("\"", "\"", false),
+                quote("'", "'", false),
+                quote("`", "`", true),
+            ],
+            comment_after_space: false,
+            markup: false,
+            char_literals: false,
+        },
+        Syntax {
+            names: &["typescript", "ts", "tsx"],
+            keywords: &TS_KEYWORDS,
+            line_comments: &["//"],
+            block_comments: &[("/*", "*/")],
+            quotes: &[
+                quote("\"", "\"", false),
+                quote("'", "'", false),
+                quote("`", "`", true),
+            ],
+            comment_after_space: false,
+            markup: false,
+            char_literals: false,
+        },
+        Syntax {
+            names: &["python", "py"],
+            keywords: &[
+                "and", "as", "assert", "async", "await", "break", "class", "continue", "def",
+                "del", "elif", "else", "except", "False", "finally", "for", "from", "global", "if",
+                "import", "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise",
+                "return", "True", "try", "while", "with", "yield",
+            ],
+            line_comments: &["#"],
+            block_comments: &[],
+            quotes: &[
+                quote("\"\"\"", "\"\"\"", true),
+                quote("'''", "'''", true),
+                quote("\"", "\"", false),
+                quote("'", "'", false),
+            ],
+            comment_after_space: false,
+            markup: false,
+            char_literals: false,
+        },
+        Syntax {
+            names: &["html", "htm", "xml", "svg"],
+            keywords: &[],
+            line_comments: &[],
+            block_comments: &[("<!--", "-->")],
+            quotes: &C_QUOTES,
+            comment_after_space: false,
+            markup: true,
+            char_literals: false,
+        },
+        Syntax {
+            names: &["css", "scss"],
+            keywords: &[
+                "important",
+                "inherit",
+                "initial",
+                "unset",
+                "none",
+                "auto",
+                "media",
+                "import",
+                "keyframes",
+                "supports",
+                "font-face",
+            ],
+            line_comments: &[],
+            block_comments: &[("/*", "*/")],
+            quotes: &C_QUOTES,
+            comment_after_space: false,
+            markup: false,
+            char_literals: false,
+        },
+        Syntax {
+            names: &["shell", "sh", "bash", "zsh", "console"],
+            keywords: &[
+                "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case",
+                "esac", "in", "function", "return", "export", "local", "readonly", "source",
+                "echo", "exit",
+            ],
+            line_comments: &["#"],
+            block_comments: &[],
+            quotes: &[quote("\"", "\"", true), quote("'", "'", true)],
+            comment_after_space: true,
+            markup: false,
+            char_literals: false,
+        },
+        Syntax {
+            names: &["json", "jsonc"],
+            keywords: &["true", "false", "null"],
+            line_comments: &[],
+            block_comments: &[],
+            quotes: &[quote("\"", "\"", false)],
+            comment_after_space: false,
+            markup: false,
+            char_literals: false,
+        },
+        Syntax {
+            names: &["yaml", "yml"],
+            keywords: &["true", "false", "null", "yes", "no", "on", "off"],
+            line_comments: &["#"],
+            block_comments: &[],
+            quotes: &C_QUOTES,
+            comment_after_space: true,
+            markup: false,
+            char_literals: false,
+        },
+    ];
+
+    impl Syntax {
+        fn find(language: &str) -> Option<&'static Syntax> {
+            let language = language.to_ascii_lowercase();
+            SYNTAXES
+                .iter()
+                .find(|syntax| syntax.names.contains(&language.as_str()))
+        }
+
+        /// Length of the comment at the head of `rest`.
+        fn comment(&self, rest: &str, after_space: bool) -> Option<usize> {
+            if let Some((open, close)) = self
+                .block_comments
+                .iter()
+                .find(|(open, _)| rest.starts_with(open))
+            {
+                return Some(match rest[open.len()..].find(close) {
+                    Some(at) => open.len() + at + close.len(),
+                    None => rest.len(),
+                });
+            }
+            if (after_space || !self.comment_after_space)
+                && self.line_comments.iter().any(|open| rest.starts_with(open))
+            {
+                return Some(rest.find('\n').unwrap_or(rest.len()));
+            }
+            None
+        }
+
+        /// Length of the string literal at the head of `rest`.
+        fn string(&self, rest: &str) -> Option<usize> {
+            let quote = self.quotes.iter().find(|q| rest.starts_with(q.open))?;
+            let mut chars = rest[quote.open.len()..].char_indices();
+            while let Some((at, c)) = chars.next() {
+                let at = quote.open.len() + at;
+                if c == '\\' {
+                    chars.next();
+                } else if rest[at..].starts_with(quote.close) {
+                    return Some(at + quote.close.len());
+                } else if c == '\n' && !quote.multiline {
+                    return Some(at);
+                }
+            }
+            Some(rest.len())
+        }
+
+        /// Length of the char literal at the head of `rest`, such as `'x'` or `'\n'`.
+        fn char_literal(&self, rest: &str) -> Option<usize> {
+            let body = rest.strip_prefix('\'').filter(|_| self.char_literals)?;
+            let len = match body.strip_prefix('\\') {
+                Some(escaped) => {
+                    let c = escaped.chars().next()?;
+                    let line = escaped[c.len_utf8()..].split('\n').next()?;
+                    1 + c.len_utf8() + line.find('\'')?
+                }
+                None => body.chars().next()?.len_utf8(),
+            };
+            body[len..].starts_with('\'').then_some(len + 2)
+        }
+
+        /// Length of the lifetime at the head of `rest`, such as `'a` or `'static`.
+        fn lifetime(&self, rest: &str) -> Option<usize> {
+            let name = rest.strip_prefix('\'').filter(|_| self.char_literals)?;
+            let len = name
+                .char_indices()
+                .find(|(_, c)| !c.is_alphanumeric() && *c != '_')
+                .map_or(name.len(), |(at, _)| at);
+            (len > 0).then_some(len + 1)
+        }
+    }
+
+    fn is_word_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_' || c == '-'
+    }
+
+    /// Highlight `code` written in `language`, or `None` when the language is not supported.
+    ///
+    /// Keywords, strings and comments are wrapped with `span` of
+    /// `{class_prefix}-hl-keyword`, `-hl-string` and `-hl-comment`, and the others are escaped.
+    pub fn highlight(code: &str, language: &str, class_prefix: &str) -> Option<String> {
+        let syntax = Syntax::find(language)?;
+        let mut rslt = String::new();
+        let mut push = |kind: Option<TokenKind>, text: &str| match kind {
+            Some(kind) => rslt.push_str(&format!(
+                r#"<span class="{}-{}">{}</span>"#,
+                class_prefix,
+                kind.class_name(),
+                escape_code_string(text)
+            )),
+            None => rslt.push_str(&escape_code_string(text)),
+        };
+        let mut in_tag = false;
+        let mut i = 0;
+        while let Some(c) = code[i..].chars().next() {
+            let rest = &code[i..];
+            let previous = code[..i].chars().next_back();
+            let after_space = previous.is_none_or(char::is_whitespace);
+            let (kind, len) = if let Some(len) = syntax.comment(rest, after_space) {
+                (Some(TokenKind::Comment), len)
+            } else if let Some(len) = (!syntax.markup || in_tag)
+                .then(|| syntax.string(rest).or_else(|| syntax.char_literal(rest)))
+                .flatten()
+            {
+                (Some(TokenKind::String), len)
+            } else if let Some(len) = syntax.lifetime(rest) {
+                // name of the lifetime is not a keyword even for `'static`
+                (None, len)
+            } else if is_word_char(c) && c != '-' {
+                let len = rest
+                    .char_indices()
+                    .find(|(_, c)| !is_word_char(*c))
+                    .map_or(rest.len(), |(at, _)| at);
+                let word = &rest[..len];
+                let is_keyword = if syntax.markup {
+                    matches!(previous, Some('<') | Some('/')) && in_tag
+                } else {
+                    syntax.keywords.contains(&word)
+                };
+                (is_keyword.then_some(TokenKind::Keyword), len)
+            } else {
+                if syntax.markup {
+                    match c {
+                        '<' => in_tag = true,
+                        '>' => in_tag = false,
+                        _ => {}
+                    }
+                }
+                (None, c.len_utf8())
+            };
+            push(kind, &rest[..len]);
+            i += len;
+        }
+        Some(rslt)
+    }
+
+    #[cfg(test)]
+    mod test_highlight {
+        use super::*;
+
+        #[test]
+        fn test_highlight() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                language: String,
+                input: String,
+                expected: Option<String>,
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should highlight rust"),
+                    language: String::from("rust"),
+                    input: String::from("fn main() { let s = \"a\\\"b\"; } // done"),
+                    expected: Some(String::from(
+                        r#"<span class="p-hl-keyword">fn</span> main() { <span class="p-hl-keyword">let</span> s = <span class="p-hl-string">&quot;a\&quot;b&quot;</span>; } <span class="p-hl-comment">// done</span>"#,
+                    )),
+                },
+                TestCase {
+                    it: String::from("should highlight char literals of rust"),
+                    language: String::from("rust"),
+                    input: String::from(r"let c = ['x', '\n', '\'', '\u{1F600}'];"),
+                    expected: Some(String::from(
+                        r#"<span class="p-hl-keyword">let</span> c = [<span class="p-hl-string">&#39;x&#39;</span>, <span class="p-hl-string">&#39;\n&#39;</span>, <span class="p-hl-string">&#39;\&#39;&#39;</span>, <span class="p-hl-string">&#39;\u{1F600}&#39;</span>];"#,
+                    )),
+                },
+                TestCase {
+                    it: String::from("should not highlight lifetimes of rust"),
+                    language: String::from("rust"),
+                    input: String::from("fn f<'a>(s: &'a str) -> &'static str { s }"),
+                    expected: Some(String::from(
+                        r#"<span class="p-hl-keyword">fn</span> f&lt;&#39;a&gt;(s: &amp;&#39;a str) -&gt; &amp;&#39;static str { s }"#,
+                    )),
+                },
+                TestCase {
+                    it: String::from("should highlight keywords of typescript and javascript"),
+                    language: String::from("ts"),
+                    input: String::from("interface A extends B {}"),
+                    expected: Some(String::from(
+                        r#"<span class="p-hl-keyword">interface</span> A <span class="p-hl-keyword">extends</span> B {}"#,
+                    )),
+                },
+                TestCase {
+                    it: String::from("should not highlight keyword in word"),
+                    language: String::from("py"),
+                    input: String::from("iffy = None # if"),
+                    expected: Some(String::from(
+                        r#"iffy = <span class="p-hl-keyword">None</span> <span class="p-hl-comment"># if</span>"#,
+                    )),
+                },
+                TestCase {
+                    it: String::from("should highlight tag names and attributes of html"),
+                    language: String::from("html"),
+                    input: String::from(r#"<a href="x">it's</a><!-- c -->"#),
+                    expected: Some(String::from(
+                        r#"&lt;<span class="p-hl-keyword">a</span> href=<span class="p-hl-string">&quot;x&quot;</span>&gt;it&#39;s&lt;/<span class="p-hl-keyword">a</span>&gt;<span class="p-hl-comment">&lt;!-- c --&gt;</span>"#,
+                    )),
+                },
+                TestCase {
+                    it: String::from("should start shell comment only after space"),
+                    language: String::from("bash"),
+                    input: String::from("echo $# # args"),
+                    expected: Some(String::from(
+                        r#"<span class="p-hl-keyword">echo</span> $# <span class="p-hl-comment"># args</span>"#,
+                    )),
+                },
+                TestCase {
+                    it: String::from("should return none for unknown language"),
+                    language: String::from("brainfuck"),
+                    input: String::from("+[-]"),
+                    expected: None,
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let output = highlight(&test_case.input, &test_case.language, "p");
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+    }
+}
@@ -1,15 +1,29 @@
 pub mod lexer {
     use crate::lexer::builder::builder::MdNode;
-    use crate::lexer::pattern::code_block::code_block::{is_code_block_start, parse_code_block};
-    use crate::lexer::pattern::inline::inline::inline_parse;
-    use crate::lexer::pattern::list::list::ListPattern::SimpleList;
+    use crate::lexer::error::error::{Diagnostics, ParseError};
+    use crate::lexer::pattern::code_block::code_block::{
+        is_code_block_start, parse_code_block, parse_fence,
+    };
+    use crate::lexer::pattern::heading::heading::{
+        is_atx_heading, parse_atx_heading, setext_heading_tag,
+    };
+    use crate::lexer::pattern::html_block::html_block::{
+        html_block_len, interrupts_paragraph, is_html_block_start,
+    };
+    use crate::lexer::pattern::inline::inline::inline_parse_lines;
     use crate::lexer::pattern::list::list::{
-        is_number_list, is_simple_list, parse_list, ListPattern,
+        is_number_list, is_simple_list, list_block_len, list_interrupts_paragraph, parse_list,
+        ListPattern,
     };
     use crate::lexer::pattern::quote::quote::{enclose_quote, is_quote_block};
     use crate::lexer::pattern::table::table::{is_table_block_start, parse_table};
+    use crate::lexer::pattern::thematic_break::thematic_break::is_thematic_break;
+    use crate::lexer::source::source::{lines_span, source_lines, Line, Span};
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub enum Token {
         H1,
         H2,
@@ -24,6 +38,8 @@ pub mod lexer {
         Blockquote,
         Code,
         Table,
+        Hr,
+        Html,
     }
 
     impl Token {
@@ -42,15 +58,17 @@ pub mod lexer {
                 Token::Blockquote => "Blockquote",
                 Token::Code => "code",
                 Token::Table => "table",
+                Token::Hr => "hr",
+                Token::Html => "html",
             }
             .to_string()
         }
 
         pub fn is_head(&self) -> bool {
-            match *self {
-                Token::H1 | Token::H2 | Token::H3 | Token::H4 | Token::H5 | Token::H6 => true,
-                _ => false,
-            }
+            matches!(
+                *self,
+                Token::H1 | Token::H2 | Token::H3 | Token::H4 | Token::H5 | Token::H6
+            )
         }
 
         pub fn value_of(number: i8) -> Self {
@@ -66,35 +84,66 @@ pub mod lexer {
         }
     }
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct Table {
         pub head: Vec<TableHead>,
-        pub body: Vec<Vec<String>>,
+        pub body: Vec<TableRow>,
     }
 
+    /// Cells of a row of the table body, each of which is parsed as inline content.
+    pub type TableRow = Vec<Vec<InlineNode>>;
+
     impl Table {
-        pub fn new(head: Vec<TableHead>, body: Vec<Vec<String>>) -> Self {
+        pub fn new(head: Vec<TableHead>, body: Vec<TableRow>) -> Self {
             Table { head, body }
         }
     }
 
-    #[derive(Debug, PartialEq)]
+    #[cfg(test)]
+    impl Table {
+        /// Same table with every span cleared, to compare the structure only.
+        pub fn without_span(self) -> Self {
+            let cell_without_span =
+                |cell: Vec<InlineNode>| cell.into_iter().map(InlineNode::without_span).collect();
+            Table {
+                head: self
+                    .head
+                    .into_iter()
+                    .map(|head| TableHead::new(cell_without_span(head.cell), head.align))
+                    .collect(),
+                body: self
+                    .body
+                    .into_iter()
+                    .map(|row| row.into_iter().map(cell_without_span).collect())
+                    .collect(),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct TableHead {
-        pub cell: String,
+        pub cell: Vec<InlineNode>,
         align: Align,
     }
 
     impl TableHead {
-        pub fn new(cell: String, align: Align) -> Self {
+        pub fn new(cell: Vec<InlineNode>, align: Align) -> Self {
             TableHead { cell, align }
         }
 
         pub fn get_align(&self) -> String {
             self.align.value()
         }
+
+        pub fn align(&self) -> Align {
+            self.align
+        }
     }
 
     #[derive(Debug, Copy, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub enum Align {
         Center,
         Left,
@@ -112,20 +161,143 @@ pub mod lexer {
         }
     }
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum InlineNode {
+        Text {
+            value: String,
+            span: Span,
+        },
+        Emphasis {
+            children: Vec<InlineNode>,
+            span: Span,
+        },
+        Strong {
+            children: Vec<InlineNode>,
+            span: Span,
+        },
+        Code {
+            value: String,
+            span: Span,
+        },
+        Link {
+            href: String,
+            title: Option<String>,
+            children: Vec<InlineNode>,
+            span: Span,
+        },
+        Image {
+            src: String,
+            alt: String,
+            title: Option<String>,
+            span: Span,
+        },
+        /// Line ending inside a paragraph.
+        SoftBreak {
+            span: Span,
+        },
+        /// Line ending after two spaces or a backslash.
+        HardBreak {
+            span: Span,
+        },
+        /// Raw html tag, comment or declaration, kept as written.
+        Html {
+            value: String,
+            span: Span,
+        },
+    }
+
+    impl InlineNode {
+        /// Range of the source text.
+        pub fn span(&self) -> &Span {
+            match self {
+                InlineNode::Text { span, .. }
+                | InlineNode::Emphasis { span, .. }
+                | InlineNode::Strong { span, .. }
+                | InlineNode::Code { span, .. }
+                | InlineNode::Link { span, .. }
+                | InlineNode::Image { span, .. }
+                | InlineNode::SoftBreak { span }
+                | InlineNode::HardBreak { span }
+                | InlineNode::Html { span, .. } => span,
+            }
+        }
+
+        pub fn span_mut(&mut self) -> &mut Span {
+            match self {
+                InlineNode::Text { span, .. }
+                | InlineNode::Emphasis { span, .. }
+                | InlineNode::Strong { span, .. }
+                | InlineNode::Code { span, .. }
+                | InlineNode::Link { span, .. }
+                | InlineNode::Image { span, .. }
+                | InlineNode::SoftBreak { span }
+                | InlineNode::HardBreak { span }
+                | InlineNode::Html { span, .. } => span,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    impl InlineNode {
+        /// Same node with every span cleared, to compare the structure only.
+        pub fn without_span(mut self) -> Self {
+            *self.span_mut() = Span::default();
+            if let InlineNode::Emphasis { children, .. }
+            | InlineNode::Strong { children, .. }
+            | InlineNode::Link { children, .. } = &mut self
+            {
+                *children = std::mem::take(children)
+                    .into_iter()
+                    .map(InlineNode::without_span)
+                    .collect();
+            }
+            self
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub enum Content {
-        PlainText { value: String },
-        ElementNode { value: ElementNode },
-        ElementNodes { value: Vec<ElementNode> },
-        Table { value: Table },
+        /// Code of a code block, with the language and the rest of the info string of its fence.
+        CodeBlock {
+            value: String,
+            language: Option<String>,
+            meta: Option<String>,
+        },
+        InlineNodes {
+            value: Vec<InlineNode>,
+        },
+        /// Blocks in list item. `checked` is `Some` for task list item such as `- [x]`.
+        ListItem {
+            value: Vec<ElementNode>,
+            checked: Option<bool>,
+        },
+        ElementNodes {
+            value: Vec<ElementNode>,
+        },
+        /// Items of list. `start` is the number of the first item of ordered list.
+        ///
+        /// `loose` is true when a blank line separates the items or the blocks in them,
+        /// and their paragraphs are wrapped with `p`.
+        List {
+            value: Vec<ElementNode>,
+            start: Option<u32>,
+            loose: bool,
+        },
+        Table {
+            value: Table,
+        },
     }
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub enum ElementNode {
         Exist {
             tag: Token,
             content: Box<Content>,
             children: Box<ElementNode>,
+            span: Span,
         },
         Nil,
     }
@@ -136,6 +308,77 @@ pub mod lexer {
                 tag,
                 content: Box::new(content),
                 children,
+                span: Span::default(),
+            }
+        }
+
+        pub fn with_span(self, span: Span) -> Self {
+            match self {
+                ElementNode::Exist {
+                    tag,
+                    content,
+                    children,
+                    ..
+                } => ElementNode::Exist {
+                    tag,
+                    content,
+                    children,
+                    span,
+                },
+                ElementNode::Nil => ElementNode::Nil,
+            }
+        }
+
+        /// Range of the source text, or `None` for [`ElementNode::Nil`].
+        pub fn span(&self) -> Option<&Span> {
+            match self {
+                ElementNode::Exist { span, .. } => Some(span),
+                ElementNode::Nil => None,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    impl ElementNode {
+        /// Same node with every span cleared, to compare the structure only.
+        pub fn without_span(self) -> Self {
+            match self {
+                ElementNode::Exist {
+                    tag,
+                    content,
+                    children,
+                    ..
+                } => ElementNode::Exist {
+                    tag,
+                    content: Box::new(match *content {
+                        Content::InlineNodes { value } => Content::InlineNodes {
+                            value: value.into_iter().map(InlineNode::without_span).collect(),
+                        },
+                        Content::ListItem { value, checked } => Content::ListItem {
+                            value: value.into_iter().map(ElementNode::without_span).collect(),
+                            checked,
+                        },
+                        Content::ElementNodes { value } => Content::ElementNodes {
+                            value: value.into_iter().map(ElementNode::without_span).collect(),
+                        },
+                        Content::List {
+                            value,
+                            start,
+                            loose,
+                        } => Content::List {
+                            value: value.into_iter().map(ElementNode::without_span).collect(),
+                            start,
+                            loose,
+                        },
+                        Content::Table { value } => Content::Table {
+                            value: value.without_span(),
+                        },
+                        content => content,
+                    }),
+                    children: Box::new(children.without_span()),
+                    span: Span::default(),
+                },
+                ElementNode::Nil => ElementNode::Nil,
             }
         }
     }
@@ -152,18 +395,36 @@ pub mod lexer {
 
     #[macro_export]
     macro_rules! element_node {
-        (tag: $tag:expr, content: $content:expr, children: $children:expr $(,)? ) => {
+        (tag: $tag:expr, content: $content:expr, children: $children:expr, span: $span:expr $(,)? ) => {
             ElementNode::Exist {
                 tag: $tag,
                 content: Box::new($content),
                 children: Box::new($children),
+                span: $span,
+            }
+        };
+        (tag: $tag:expr, content: $content:expr, children: $children:expr $(,)? ) => {
+            element_node! {
+                tag: $tag,
+                content: $content,
+                children: $children,
+                span: Default::default(),
+            }
+        };
+        (tag: $tag:expr, content: $content:expr, span: $span:expr $(,)? ) => {
+            element_node! {
+                tag: $tag,
+                content: $content,
+                children: element_node!(),
+                span: $span,
             }
         };
         (tag: $tag:expr, content: $content:expr $(,)? ) => {
-            ElementNode::Exist {
+            element_node! {
                 tag: $tag,
-                content: Box::new($content),
-                children: Box::new(element_node!()),
+                content: $content,
+                children: element_node!(),
+                span: Default::default(),
             }
         };
         () => {
@@ -179,121 +440,310 @@ pub mod lexer {
     }
 
     #[macro_export]
-    macro_rules! content_plain_text {
+    macro_rules! content_list {
+        (start: $start:expr $(, $x : expr) * $(,) ? ) => {
+            Content::List { value: vec![$($x), *], start: Some($start), loose: false }
+        };
+        ($($x : expr), * $(,) ? ) => {
+            Content::List { value: vec![$($x), *], start: None, loose: false }
+        };
+    }
+
+    #[macro_export]
+    macro_rules! content_code_block {
+        ($value:expr, language: $language:expr, meta: $meta:expr $(,)? ) => {
+            Content::CodeBlock {
+                value: $value,
+                language: $language,
+                meta: $meta,
+            }
+        };
         ($value:expr $(,)? ) => {
-            Content::PlainText { value: $value }
+            $crate::content_code_block!($value, language: None, meta: None)
+        };
+    }
+
+    #[macro_export]
+    macro_rules! content_inline_nodes {
+        ($($x : expr), * $(,) ? ) => {
+            Content::InlineNodes { value: vec![$($x), *] }
+        };
+    }
+
+    #[macro_export]
+    macro_rules! content_list_item {
+        (checked: $checked:expr $(, $x : expr) * $(,) ? ) => {
+            Content::ListItem { value: vec![$($x), *], checked: Some($checked) }
+        };
+        ($($x : expr), * $(,) ? ) => {
+            Content::ListItem { value: vec![$($x), *], checked: None }
         };
     }
 
-    fn parse_line(input: &String) -> ElementNode {
-        let mut sharp_count: i8 = 0;
-        for char in input.as_str().chars() {
-            if char == '#' {
-                sharp_count += 1;
-            } else if char == ' ' {
-                break;
+    #[macro_export]
+    macro_rules! inline_text {
+        ($value:expr, span: $span:expr $(,)? ) => {
+            InlineNode::Text {
+                value: $value.to_string(),
+                span: $span,
             }
+        };
+        ($value:expr $(,)? ) => {
+            inline_text!($value, span: Default::default())
+        };
+    }
+
+    /// Parse `content` of heading, which is lines above the underline for setext heading.
+    fn parse_heading(tag: Token, content: &[Line], span: Span) -> ElementNode {
+        element_node! {
+            tag: tag,
+            content: Content::InlineNodes {
+                value: inline_parse_lines(content),
+            },
+            span: span,
         }
-        let content = if sharp_count == 0 {
-            input
-        } else {
-            &input[(sharp_count as usize) + 1..]
-        }
-        .to_string();
+    }
+
+    fn is_blank(input: &str) -> bool {
+        input.trim().is_empty()
+    }
+
+    /// Whether `input` continues the paragraph above instead of starting another block.
+    pub(crate) fn is_paragraph_continuation(input: &str) -> bool {
+        !is_blank(input)
+            && !is_atx_heading(input)
+            && !list_interrupts_paragraph(input)
+            && !is_quote_block(input)
+            && !is_code_block_start(input)
+            && !is_table_block_start(input)
+            && !is_thematic_break(input)
+            && !interrupts_paragraph(input)
+    }
+
+    /// Parse consecutive lines of text as one paragraph.
+    fn parse_paragraph(input: &[Line]) -> ElementNode {
         element_node! {
-            tag: Token::value_of(sharp_count),
-            content: content_plain_text!(inline_parse(&content)),
+            tag: Token::P,
+            content: Content::InlineNodes {
+                value: inline_parse_lines(input),
+            },
+            span: lines_span(input),
         }
     }
 
-    fn parse(input: &Vec<String>) -> Vec<ElementNode> {
+    /// Nesting of lists and quotes, beyond which their markers are paragraph text.
+    const MAX_NESTING_DEPTH: usize = 32;
+
+    /// Parse `input` as blocks, which is also used for the content of quotes and list items.
+    ///
+    /// `depth` is the count of the lists and quotes enclosing `input`.
+    pub(crate) fn parse(
+        input: &[Line],
+        depth: usize,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Vec<ElementNode>, ParseError> {
+        // deeper blocks are not parsed, which bounds the recursion and rescanning of the lines
+        let can_nest = depth < MAX_NESTING_DEPTH;
         let mut element_nodes: Vec<ElementNode> = vec![];
         let mut i: usize = 0;
         while i < input.len() {
             let list_index = i;
-            if is_simple_list(input.get(i).unwrap()) {
-                while is_simple_list(input.get(i).unwrap()) {
-                    i += 1;
-                }
-                if list_index != i {
-                    let parse_result =
-                        parse_list(input[list_index..i].to_vec(), ListPattern::SimpleList, 0);
-                    element_nodes.push(parse_result);
-                    continue;
-                }
-            } else if is_number_list(input.get(i).unwrap()) {
-                while is_number_list(input.get(i).unwrap()) {
-                    i += 1;
-                }
-                if list_index != i {
-                    let parse_result =
-                        parse_list(input[list_index..i].to_vec(), ListPattern::NumberList, 0);
-                    element_nodes.push(parse_result);
-                    continue;
-                }
-            } else if is_quote_block(input.get(i).unwrap()) {
+            if is_blank(&input[i].text) {
+                // blank lines only separate blocks
+                i += 1;
+                continue;
+            } else if is_thematic_break(&input[i].text) {
+                element_nodes.push(element_node! {
+                    tag: Token::Hr,
+                    content: Content::InlineNodes { value: vec![] },
+                    span: input[i].span(),
+                });
+                i += 1;
+                continue;
+            } else if can_nest && is_simple_list(&input[i].text) {
+                i += list_block_len(&input[i..], ListPattern::SimpleList);
+                element_nodes.push(parse_list_block(
+                    &input[list_index..i],
+                    ListPattern::SimpleList,
+                    depth,
+                    diagnostics,
+                )?);
+                continue;
+            } else if can_nest && is_number_list(&input[i].text) {
+                i += list_block_len(&input[i..], ListPattern::NumberList);
+                element_nodes.push(parse_list_block(
+                    &input[list_index..i],
+                    ListPattern::NumberList,
+                    depth,
+                    diagnostics,
+                )?);
+                continue;
+            } else if can_nest && is_quote_block(&input[i].text) {
                 let quote_start = i;
-                while i < input.len() && input.get(i).unwrap() != "" {
+                while i < input.len() && !is_blank(&input[i].text) {
                     i += 1;
                 }
-                let parse_result = parse(&enclose_quote(input[quote_start..i].to_vec()));
+                let parse_result = parse(
+                    &enclose_quote(&input[quote_start..i]),
+                    depth + 1,
+                    diagnostics,
+                )?;
                 element_nodes.push(element_node! {
                     tag: Token::Blockquote,
                     content: Content::ElementNodes { value: parse_result },
+                    span: lines_span(&input[quote_start..i]),
                 });
                 i += 1;
                 continue;
-            } else if is_code_block_start(input.get(i).unwrap()) {
-                i += 1;
-                let code_block_start = i;
-                while !is_code_block_start(input.get(i).unwrap()) {
+            } else if let Some(fence) = parse_fence(&input[i].text) {
+                let code_block_start = i + 1;
+                i = code_block_start;
+                while i < input.len() && !fence.is_closed_by(&input[i].text) {
                     i += 1;
                 }
+                if i == input.len() {
+                    let fence = &input[code_block_start - 1].start;
+                    diagnostics.report(ParseError::UnterminatedCodeBlock {
+                        line: fence.line,
+                        column: fence.column,
+                    })?;
+                }
+                let code_block_end = (i + 1).min(input.len());
                 element_nodes.push(element_node! {
                     tag: Token::Code,
-                    content: Content::PlainText{
-                        value: parse_code_block(input[code_block_start..i].to_vec()).join("<br />"),
+                    content: Content::CodeBlock {
+                        value: parse_code_block(&input[code_block_start..i], fence.indent),
+                        language: fence.language(),
+                        meta: fence.meta(),
                     },
+                    span: lines_span(&input[code_block_start - 1..code_block_end]),
                 });
                 i += 1;
                 continue;
-            } else if is_table_block_start(input.get(i).unwrap()) {
-                let (table, skip) = parse_table(input[i..].to_vec());
-                i += skip;
+            } else if is_html_block_start(&input[i].text) {
+                let html_block_end = i + html_block_len(&input[i..]);
+                let span = lines_span(&input[i..html_block_end]);
+                let value = input[i..html_block_end]
+                    .iter()
+                    .map(|line| line.text.as_str())
+                    .collect::<Vec<&str>>()
+                    .join("\n");
+                element_nodes.push(element_node! {
+                    tag: Token::Html,
+                    content: Content::InlineNodes {
+                        value: vec![InlineNode::Html { value, span }],
+                    },
+                    span: span,
+                });
+                i = html_block_end;
+                continue;
+            } else if is_table_block_start(&input[i].text) {
+                let (table, skip) = parse_table(&input[i..], diagnostics)?;
                 element_nodes.push(element_node! {
                     tag: Token::Table,
                     content: Content::Table {
                         value: table
                     },
+                    span: lines_span(&input[i..i + skip]),
                 });
+                i += skip;
+                continue;
             }
-            element_nodes.push(parse_line(input.get(i).unwrap()));
+            if let Some((tag, content)) = parse_atx_heading(&input[i]) {
+                element_nodes.push(parse_heading(tag, &[content], input[i].span()));
+                i += 1;
+                continue;
+            }
+            let paragraph_start = i;
             i += 1;
+            let mut setext_tag = None;
+            while i < input.len() {
+                // the underline takes precedence over thematic break, so `---` here is heading
+                setext_tag = setext_heading_tag(&input[i].text);
+                if setext_tag.is_some() || !is_paragraph_continuation(&input[i].text) {
+                    break;
+                }
+                i += 1;
+            }
+            match setext_tag {
+                Some(tag) => {
+                    element_nodes.push(parse_heading(
+                        tag,
+                        &input[paragraph_start..i],
+                        lines_span(&input[paragraph_start..=i]),
+                    ));
+                    i += 1;
+                }
+                None => element_nodes.push(parse_paragraph(&input[paragraph_start..i])),
+            }
         }
-        element_nodes
+        Ok(element_nodes)
+    }
+
+    fn parse_list_block(
+        input: &[Line],
+        pattern: ListPattern,
+        depth: usize,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<ElementNode, ParseError> {
+        let indent = pattern.indent(&input[0].text).unwrap_or(0);
+        parse_list(input, pattern, indent, depth, diagnostics)
     }
 
     pub struct Lexer {
-        text: Vec<String>,
+        text: Vec<Line>,
     }
 
     impl Lexer {
+        /// Generate lexer for `text`, which is markdown split by `\n`.
         pub fn new(text: Vec<String>) -> Self {
-            Lexer { text }
+            Lexer {
+                text: source_lines(text),
+            }
+        }
+
+        fn parse_with(&self, mut diagnostics: Diagnostics) -> Result<MdNode, ParseError> {
+            let result_str = parse(&self.text, 0, &mut diagnostics)?;
+            Ok(MdNode::new(result_str).with_diagnostics(diagnostics.into_errors()))
         }
 
+        /// Parse markdown text.
+        ///
+        /// This never fails. Malformed markdown is recovered and reported
+        /// by [`MdNode::diagnostics`].
         pub fn parse(&self) -> MdNode {
-            let result_str = parse(&self.text);
-            MdNode::new(result_str)
+            match self.parse_with(Diagnostics::lenient()) {
+                Ok(md_node) => md_node,
+                Err(_) => unreachable!("lenient parsing never returns error"),
+            }
+        }
+
+        /// Parse markdown text, returning [`ParseError`] when the markdown is malformed.
+        pub fn try_parse(&self) -> Result<MdNode, ParseError> {
+            self.parse_with(Diagnostics::strict())
         }
     }
 
     #[cfg(test)]
     mod test_lexer {
         use super::*;
+        use crate::lexer::pattern::inline::inline::text_content;
+        use crate::lexer::source::source::Position;
         use crate::vec_string;
         use pretty_assertions::assert_eq;
 
+        fn without_span(md_node: MdNode) -> MdNode {
+            MdNode::new(
+                md_node
+                    .element_nodes()
+                    .iter()
+                    .cloned()
+                    .map(ElementNode::without_span)
+                    .collect(),
+            )
+            .with_diagnostics(md_node.diagnostics().to_vec())
+        }
+
         #[test]
         fn test_parse() {
             let input = vec_string![
@@ -310,6 +760,7 @@ pub mod lexer {
                 "  1. aaa",
                 "  2. ccc",
                 "    1. ddd",
+                "",
                 "this is [Google先生](https://example.com)",
                 "画像 ![エビフライトライアングル](https://example.com)",
                 "> aaa",
@@ -335,167 +786,909 @@ pub mod lexer {
             let expected = MdNode::new(vec![
                 element_node! {
                     tag: Token::H1,
-                    content: content_plain_text!("hello".to_string()),
+                    content: content_inline_nodes![inline_text!("hello")],
                 },
                 element_node! {
                     tag: Token::H2,
-                    content: content_plain_text!("world".to_string()),
+                    content: content_inline_nodes![inline_text!("world")],
                 },
                 element_node! {
                     tag: Token::Ul,
-                    content: content_element_nodes![
+                    content: content_list![
                         element_node! {
                             tag: Token::Li,
-                            content: content_plain_text!("hogehoge".to_string()),
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("hogehoge")],
+                                },
+                            ],
                         },
                         element_node! {
                             tag: Token::Li,
-                            content: content_plain_text!("hogehoge1".to_string()),
-                            children: element_node! {
-                                tag: Token::Ul,
-                                content: content_element_nodes![
-                                    element_node! {
-                                        tag: Token::Li,
-                                        content: content_plain_text!(r#"this is <a class="flav-md-a" href="https://example.com" alt="Google先生">Google先生</a>"#.to_string()),
-                                    },
-                                    element_node! {
-                                        tag: Token::Li,
-                                        content: content_plain_text!("hogehoge3".to_string()),
-                                        children: element_node! {
-                                            tag: Token::Ul,
-                                            content: content_element_nodes![
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("hogehoge1")],
+                                },
+                                element_node! {
+                                    tag: Token::Ul,
+                                    content: content_list![
+                                        element_node! {
+                                            tag: Token::Li,
+                                            content: content_list_item![
+                                                element_node! {
+                                                    tag: Token::P,
+                                                    content: content_inline_nodes![inline_text!("this is "),
+                                    InlineNode::Link {
+                                        href: "https://example.com".to_string(),
+                                        title: None,
+                                        children: vec![inline_text!("Google先生")],
+                                        span: Default::default(),
+                                    }],
+                                                },
+                                            ],
+                                        },
+                                        element_node! {
+                                            tag: Token::Li,
+                                            content: content_list_item![
+                                                element_node! {
+                                                    tag: Token::P,
+                                                    content: content_inline_nodes![inline_text!("hogehoge3")],
+                                                },
                                                 element_node! {
-                                                    tag: Token::Li,
-                                                    content: content_plain_text!("hoge 4".to_string()),
-                                                }
+                                                    tag: Token::Ul,
+                                                    content: content_list![
+                                                        element_node! {
+                                                            tag: Token::Li,
+                                                            content: content_list_item![
+                                                                element_node! {
+                                                                    tag: Token::P,
+                                                                    content: content_inline_nodes![inline_text!("hoge 4")],
+                                                                },
+                                                            ],
+                                                        }
+                                                    ]
+                                                },
                                             ]
-                                        }
-                                    },
-                                ],
-                            }
+                                        },
+                                    ],
+                                },
+                            ]
                         },
                         element_node! {
                             tag: Token::Li,
-                            content: content_plain_text!("hogehoge4".to_string()),
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("hogehoge4")],
+                                },
+                            ],
                         },
                     ]
                 },
                 element_node! {
                     tag: Token::Ol,
-                    content: content_element_nodes![
+                    content: content_list![
+                        start: 1,
                         element_node! {
                             tag: Token::Li,
-                            content: content_plain_text!("hoge1".to_string()),
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("hoge1")],
+                                },
+                            ],
                         },
                         element_node! {
                             tag: Token::Li,
-                            content: content_plain_text!("hoge2".to_string()),
-                            children: element_node! {
-                                tag: Token::Ol,
-                                content: content_element_nodes![
-                                    element_node! {
-                                        tag: Token::Li,
-                                        content: content_plain_text!("aaa".to_string()),
-                                    },
-                                    element_node! {
-                                        tag: Token::Li,
-                                        content: content_plain_text!("ccc".to_string()),
-                                        children: element_node! {
-                                            tag: Token::Ol,
-                                            content: content_element_nodes![
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("hoge2")],
+                                },
+                                element_node! {
+                                    tag: Token::Ol,
+                                    content: content_list![
+                                        start: 1,
+                                        element_node! {
+                                            tag: Token::Li,
+                                            content: content_list_item![
+                                                element_node! {
+                                                    tag: Token::P,
+                                                    content: content_inline_nodes![inline_text!("aaa")],
+                                                },
+                                            ],
+                                        },
+                                        element_node! {
+                                            tag: Token::Li,
+                                            content: content_list_item![
                                                 element_node! {
-                                                    tag: Token::Li,
-                                                    content: content_plain_text!("ddd".to_string()),
-                                                }
+                                                    tag: Token::P,
+                                                    content: content_inline_nodes![inline_text!("ccc")],
+                                                },
+                                                element_node! {
+                                                    tag: Token::Ol,
+                                                    content: content_list![
+                                                        start: 1,
+                                                        element_node! {
+                                                            tag: Token::Li,
+                                                            content: content_list_item![
+                                                                element_node! {
+                                                                    tag: Token::P,
+                                                                    content: content_inline_nodes![inline_text!("ddd")],
+                                                                },
+                                                            ],
+                                                        }
+                                                    ]
+                                                },
                                             ]
-                                        }
-                                    },
-                                ],
-                            }
+                                        },
+                                    ],
+                                },
+                            ]
                         },
                     ]
                 },
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!(r#"this is <a class="flav-md-a" href="https://example.com" alt="Google先生">Google先生</a>"#.to_string()),
-                },
-                element_node! {
-                    tag: Token::P,
-                    content: content_plain_text!(r#"画像 <img class="flav-md-img" src="https://example.com" alt="エビフライトライアングル">"#.to_string()),
+                    content: content_inline_nodes![
+                        inline_text!("this is "),
+                        InlineNode::Link {
+                            href: "https://example.com".to_string(),
+                            title: None,
+                            children: vec![inline_text!("Google先生")],
+                            span: Default::default(),
+                        },
+                        InlineNode::SoftBreak {
+                            span: Default::default(),
+                        },
+                        inline_text!("画像 "),
+                        InlineNode::Image {
+                            src: "https://example.com".to_string(),
+                            alt: "エビフライトライアングル".to_string(),
+                            title: None,
+                            span: Default::default(),
+                        },
+                    ],
                 },
                 element_node! {
                     tag: Token::Blockquote,
                     content: content_element_nodes![
                         element_node! {
                             tag: Token::P,
-                            content: content_plain_text!("aaa".to_string()),
-                        },
-                        element_node! {
-                            tag: Token::P,
-                            content: content_plain_text!("bbb".to_string()),
+                            content: content_inline_nodes![
+                                inline_text!("aaa"),
+                                InlineNode::SoftBreak {
+                                    span: Default::default(),
+                                },
+                                inline_text!("bbb"),
+                            ],
                         },
                         element_node! {
                             tag: Token::Blockquote,
-                            content: content_element_nodes![
-                                element_node! {
-                                    tag: Token::P,
-                                    content: content_plain_text!("ccc".to_string()),
+                            content: content_element_nodes![element_node! {
+                                tag: Token::P,
+                                content: content_inline_nodes![
+                                    inline_text!("ccc"),
+                                    InlineNode::SoftBreak {
+                                        span: Default::default(),
+                                    },
+                                    inline_text!("ddd"),
+                                ],
+                            }],
+                        },
+                    ],
+                },
+                element_node! {
+                    tag: Token::H2,
+                    content: content_inline_nodes![inline_text!("world")],
+                },
+                element_node! {
+                    tag: Token::Code,
+                    content: content_code_block!(
+                        "<script src=\"hoge.js\"></script>\n<script src=\"hoge.js\"></script>"
+                            .to_string(),
+                        language: Some("html".to_string()),
+                        meta: None,
+                    ),
+                },
+                element_node! {
+                    tag: Token::P,
+                    content: content_inline_nodes![
+                        inline_text!("this is "),
+                        InlineNode::Code {
+                            value: "hoge".to_string(),
+                            span: Default::default(),
+                        },
+                        inline_text!(" and "),
+                        InlineNode::Code {
+                            value: "fuga".to_string(),
+                            span: Default::default(),
+                        },
+                        InlineNode::SoftBreak {
+                            span: Default::default(),
+                        },
+                        inline_text!("this is "),
+                        InlineNode::Emphasis {
+                            children: vec![inline_text!("hoge")],
+                            span: Default::default(),
+                        },
+                        InlineNode::SoftBreak {
+                            span: Default::default(),
+                        },
+                        inline_text!("this is "),
+                        InlineNode::Strong {
+                            children: vec![inline_text!("hoge")],
+                            span: Default::default(),
+                        },
+                        InlineNode::SoftBreak {
+                            span: Default::default(),
+                        },
+                        inline_text!("this is "),
+                        InlineNode::Emphasis {
+                            children: vec![
+                                inline_text!("hoge "),
+                                InlineNode::Strong {
+                                    children: vec![inline_text!("fuga")],
+                                    span: Default::default(),
                                 },
+                            ],
+                            span: Default::default(),
+                        },
+                    ],
+                },
+                element_node! {
+                    tag: Token::Table,
+                    content: Content::Table {
+                        value: table! {
+                            head: vec![
+                                TableHead::new(vec![inline_text!("head1")], Align::Center),
+                                TableHead::new(vec![inline_text!("head2")], Align::Right),
+                                TableHead::new(vec![inline_text!("head3")], Align::Left),
+                            ],
+                            body: vec![
+                                vec![
+                                    vec![inline_text!("aaa1")],
+                                    vec![inline_text!("bbb1")],
+                                    vec![inline_text!("ccc1")],
+                                ],
+                                vec![
+                                    vec![inline_text!("aaa2")],
+                                    vec![inline_text!("bbb2")],
+                                    vec![inline_text!("ccc2")],
+                                ],
+                            ],
+                        },
+                    }
+                },
+                element_node! {
+                    tag: Token::P,
+                    content: content_inline_nodes![inline_text!("aaa")],
+                },
+            ]);
+            let lex = Lexer::new(input);
+            assert_eq!(without_span(lex.parse()), expected);
+        }
+
+        #[test]
+        fn test_try_parse() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: Vec<String>,
+                expected: Result<MdNode, ParseError>,
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should parse list at the end of document"),
+                    input: vec_string!["# hello", "* hoge"],
+                    expected: Ok(MdNode::new(vec![
+                        element_node! {
+                            tag: Token::H1,
+                            content: content_inline_nodes![inline_text!("hello")],
+                        },
+                        element_node! {
+                            tag: Token::Ul,
+                            content: content_list![element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("hoge")],
+                                    },
+                                ],
+                            }],
+                        },
+                    ])),
+                },
+                TestCase {
+                    it: String::from("should return error when code block is not terminated"),
+                    input: vec_string!["# hello", "", "```rust", "fn main() {}"],
+                    expected: Err(ParseError::UnterminatedCodeBlock { line: 3, column: 1 }),
+                },
+                TestCase {
+                    it: String::from("should return error when table is malformed in quote"),
+                    input: vec_string!["# hello", "> |a|b|", "> |---|"],
+                    expected: Err(ParseError::MalformedTable { line: 3, column: 8 }),
+                },
+                TestCase {
+                    it: String::from("should return error when list indent is inconsistent"),
+                    input: vec_string!["* hoge", "    * fuga", "  * piyo"],
+                    expected: Err(ParseError::InconsistentListIndent { line: 3, column: 3 }),
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let output = Lexer::new(test_case.input.clone())
+                    .try_parse()
+                    .map(without_span);
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+
+        #[test]
+        fn test_parse_recovers_malformed_document() {
+            let input = vec_string!["```rust", "fn main() {}", "  * hoge", "* fuga"];
+            let expected = MdNode::new(vec![element_node! {
+                tag: Token::Code,
+                content: content_code_block!(
+                    "fn main() {}\n  * hoge\n* fuga".to_string(),
+                    language: Some("rust".to_string()),
+                    meta: None,
+                ),
+            }])
+            .with_diagnostics(vec![ParseError::UnterminatedCodeBlock {
+                line: 1,
+                column: 1,
+            }]);
+            assert_eq!(without_span(Lexer::new(input).parse()), expected);
+
+            let input = vec_string!["|a|b|", "|---|", "  * hoge", "* fuga"];
+            let md_node = Lexer::new(input).parse();
+            assert_eq!(
+                md_node.diagnostics(),
+                &[
+                    ParseError::MalformedTable { line: 2, column: 6 },
+                    ParseError::InconsistentListIndent { line: 4, column: 1 },
+                ]
+            );
+        }
+
+        #[test]
+        fn test_parse_escaped_marker() {
+            let input = vec_string![r"\# not a heading", r"\* not a list", r"1\. not a list"];
+            let expected = MdNode::new(vec![element_node! {
+                tag: Token::P,
+                content: content_inline_nodes![
+                    inline_text!("# not a heading"),
+                    InlineNode::SoftBreak {
+                        span: Default::default()
+                    },
+                    inline_text!("* not a list"),
+                    InlineNode::SoftBreak {
+                        span: Default::default()
+                    },
+                    inline_text!("1. not a list"),
+                ],
+            }]);
+            assert_eq!(without_span(Lexer::new(input).parse()), expected);
+        }
+
+        #[test]
+        fn test_parse_paragraph() {
+            let input = vec_string!["a  ", "  b\\", "c", "# d", "e", "* f"];
+            let expected = MdNode::new(vec![
+                element_node! {
+                    tag: Token::P,
+                    content: content_inline_nodes![
+                        inline_text!("a"),
+                        InlineNode::HardBreak {
+                            span: Default::default()
+                        },
+                        inline_text!("b"),
+                        InlineNode::HardBreak {
+                            span: Default::default()
+                        },
+                        inline_text!("c"),
+                    ],
+                },
+                element_node! {
+                    tag: Token::H1,
+                    content: content_inline_nodes![inline_text!("d")],
+                },
+                element_node! {
+                    tag: Token::P,
+                    content: content_inline_nodes![inline_text!("e")],
+                },
+                element_node! {
+                    tag: Token::Ul,
+                    content: content_list![element_node! {
+                        tag: Token::Li,
+                        content: content_list_item![
+                            element_node! {
+                                tag: Token::P,
+                                content: content_inline_nodes![inline_text!("f")],
+                            },
+                        ],
+                    }],
+                },
+            ]);
+            assert_eq!(without_span(Lexer::new(input).parse()), expected);
+        }
+
+        #[test]
+        fn test_parse_paragraph_before_ordered_list() {
+            let input = vec_string![
+                "was founded in",
+                "2019. It grew",
+                "",
+                "a",
+                "1.  ",
+                "b",
+                "*",
+                "1. c"
+            ];
+            let expected = MdNode::new(vec![
+                element_node! {
+                    tag: Token::P,
+                    content: content_inline_nodes![
+                        inline_text!("was founded in"),
+                        InlineNode::SoftBreak {
+                            span: Default::default()
+                        },
+                        inline_text!("2019. It grew"),
+                    ],
+                },
+                element_node! {
+                    tag: Token::P,
+                    content: content_inline_nodes![
+                        inline_text!("a"),
+                        InlineNode::SoftBreak {
+                            span: Default::default()
+                        },
+                        inline_text!("1."),
+                        InlineNode::HardBreak {
+                            span: Default::default()
+                        },
+                        inline_text!("b"),
+                        InlineNode::SoftBreak {
+                            span: Default::default()
+                        },
+                        inline_text!("*"),
+                    ],
+                },
+                element_node! {
+                    tag: Token::Ol,
+                    content: content_list![
+                        start: 1,
+                        element_node! {
+                            tag: Token::Li,
+                            content: content_list_item![
                                 element_node! {
                                     tag: Token::P,
-                                    content: content_plain_text!("ddd".to_string()),
+                                    content: content_inline_nodes![inline_text!("c")],
                                 },
                             ],
                         },
                     ],
                 },
+            ]);
+            assert_eq!(without_span(Lexer::new(input).parse()), expected);
+        }
+
+        #[test]
+        fn test_parse_fenced_code_block() {
+            let input = vec_string![
+                "````md title=README.md",
+                "```rust",
+                "```",
+                "````",
+                "  ~~~",
+                "    indented",
+                "   code",
+                "  ~~~",
+            ];
+            let expected = MdNode::new(vec![
                 element_node! {
-                    tag: Token::H2,
-                    content: content_plain_text!("world".to_string()),
+                    tag: Token::Code,
+                    content: content_code_block!(
+                        "```rust\n```".to_string(),
+                        language: Some("md".to_string()),
+                        meta: Some("title=README.md".to_string()),
+                    ),
                 },
                 element_node! {
                     tag: Token::Code,
-                    content: content_plain_text!("&lt;script src=&quot;hoge.js&quot;&gt;&lt;/script&gt;<br />&lt;script src=&quot;hoge.js&quot;&gt;&lt;/script&gt;".to_string()),
+                    content: content_code_block!("  indented\n code".to_string()),
                 },
+            ]);
+            assert_eq!(without_span(Lexer::new(input).parse()), expected);
+        }
+
+        #[test]
+        fn test_parse_blank_lines() {
+            let input = vec_string!["", "a", "   ", "", "b", ">", "> c", ">", "", ""];
+            let expected = MdNode::new(vec![
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!(r#"this is <code class="flav-md-code-inline">hoge</code> and <code class="flav-md-code-inline">fuga</code>"#.to_string()),
+                    content: content_inline_nodes![inline_text!("a")],
                 },
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!(r#"this is <em class="flav-md-em">hoge</em>"#.to_string()),
+                    content: content_inline_nodes![inline_text!("b")],
                 },
                 element_node! {
-                    tag: Token::P,
-                    content: content_plain_text!(r#"this is <strong class="flav-md-strong">hoge</strong>"#.to_string()),
+                    tag: Token::Blockquote,
+                    content: content_element_nodes![element_node! {
+                        tag: Token::P,
+                        content: content_inline_nodes![inline_text!("c")],
+                    }],
+                },
+            ]);
+            assert_eq!(without_span(Lexer::new(input).parse()), expected);
+        }
+
+        #[test]
+        fn test_parse_heading() {
+            let input = vec_string![
+                "  ## a ##",
+                "#tag",
+                "####### b",
+                "",
+                "c",
+                "d",
+                "===",
+                "e",
+                "---",
+                "> f",
+                "> -",
+            ];
+            let expected = MdNode::new(vec![
+                element_node! {
+                    tag: Token::H2,
+                    content: content_inline_nodes![inline_text!("a")],
                 },
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!(r#"this is <em class="flav-md-em">hoge <strong class="flav-md-strong">fuga</strong></em>"#.to_string()),
+                    content: content_inline_nodes![
+                        inline_text!("#tag"),
+                        InlineNode::SoftBreak {
+                            span: Default::default()
+                        },
+                        inline_text!("####### b"),
+                    ],
                 },
                 element_node! {
-                    tag: Token::Table,
-                    content: Content::Table {
-                        value: table! {
-                            head: vec![
-                                TableHead::new("head1".to_string(), Align::Center),
-                                TableHead::new("head2".to_string(), Align::Right),
-                                TableHead::new("head3".to_string(), Align::Left),
+                    tag: Token::H1,
+                    content: content_inline_nodes![
+                        inline_text!("c"),
+                        InlineNode::SoftBreak {
+                            span: Default::default()
+                        },
+                        inline_text!("d"),
+                    ],
+                },
+                element_node! {
+                    tag: Token::H2,
+                    content: content_inline_nodes![inline_text!("e")],
+                },
+                element_node! {
+                    tag: Token::Blockquote,
+                    content: content_element_nodes![element_node! {
+                        tag: Token::H2,
+                        content: content_inline_nodes![inline_text!("f")],
+                    }],
+                },
+            ]);
+            assert_eq!(without_span(Lexer::new(input).parse()), expected);
+        }
+
+        #[test]
+        fn test_parse_list_start_and_marker() {
+            let input = vec_string!["5. a", "6. b", "1) c", "- d", "  + e", "+ f"];
+            let expected = MdNode::new(vec![
+                element_node! {
+                    tag: Token::Ol,
+                    content: content_list![
+                        start: 5,
+                        element_node! {
+                            tag: Token::Li,
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("a")],
+                                },
                             ],
-                            body: vec![
-                                vec_string!["aaa1", "bbb1", "ccc1"],
-                                vec_string!["aaa2", "bbb2", "ccc2"],
+                        },
+                        element_node! {
+                            tag: Token::Li,
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("b")],
+                                },
                             ],
                         },
-                    }
+                    ],
+                },
+                element_node! {
+                    tag: Token::Ol,
+                    content: content_list![
+                        start: 1,
+                        element_node! {
+                            tag: Token::Li,
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("c")],
+                                },
+                            ],
+                        },
+                    ],
                 },
+                element_node! {
+                    tag: Token::Ul,
+                    content: content_list![element_node! {
+                        tag: Token::Li,
+                        content: content_list_item![
+                            element_node! {
+                                tag: Token::P,
+                                content: content_inline_nodes![inline_text!("d")],
+                            },
+                            element_node! {
+                                tag: Token::Ul,
+                                content: content_list![element_node! {
+                                    tag: Token::Li,
+                                    content: content_list_item![
+                                        element_node! {
+                                            tag: Token::P,
+                                            content: content_inline_nodes![inline_text!("e")],
+                                        },
+                                    ],
+                                }],
+                            },
+                        ],
+                    }],
+                },
+                element_node! {
+                    tag: Token::Ul,
+                    content: content_list![element_node! {
+                        tag: Token::Li,
+                        content: content_list_item![
+                            element_node! {
+                                tag: Token::P,
+                                content: content_inline_nodes![inline_text!("f")],
+                            },
+                        ],
+                    }],
+                },
+            ]);
+            assert_eq!(without_span(Lexer::new(input).parse()), expected);
+        }
+
+        #[test]
+        fn test_parse_list_item_blocks() {
+            let input = vec_string![
+                "1. a", "b", "", "   ```sh", "   ls", "   ```", "   > c", "2. d", "   - e",
+            ];
+            let expected = MdNode::new(vec![element_node! {
+                tag: Token::Ol,
+                content: Content::List {
+                    value: vec![
+                        element_node! {
+                            tag: Token::Li,
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![
+                                        inline_text!("a"),
+                                        InlineNode::SoftBreak {
+                                            span: Default::default()
+                                        },
+                                        inline_text!("b"),
+                                    ],
+                                },
+                                element_node! {
+                                    tag: Token::Code,
+                                    content: content_code_block!(
+                                        "ls".to_string(),
+                                        language: Some("sh".to_string()),
+                                        meta: None,
+                                    ),
+                                },
+                                element_node! {
+                                    tag: Token::Blockquote,
+                                    content: content_element_nodes![element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("c")],
+                                    }],
+                                },
+                            ],
+                        },
+                        element_node! {
+                            tag: Token::Li,
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("d")],
+                                },
+                                element_node! {
+                                    tag: Token::Ul,
+                                    content: content_list![element_node! {
+                                        tag: Token::Li,
+                                        content: content_list_item![element_node! {
+                                            tag: Token::P,
+                                            content: content_inline_nodes![inline_text!("e")],
+                                        }],
+                                    }],
+                                },
+                            ],
+                        },
+                    ],
+                    start: Some(1),
+                    loose: true,
+                },
+            }]);
+            assert_eq!(without_span(Lexer::new(input).parse()), expected);
+        }
+
+        #[test]
+        fn test_parse_thematic_break() {
+            let input = vec_string!["a", "***", "- b", "- - -", "___", "> ---"];
+            let expected = MdNode::new(vec![
                 element_node! {
                     tag: Token::P,
-                    content: content_plain_text!("aaa".to_string()),
+                    content: content_inline_nodes![inline_text!("a")],
+                },
+                element_node! {
+                    tag: Token::Hr,
+                    content: content_inline_nodes![],
+                },
+                element_node! {
+                    tag: Token::Ul,
+                    content: content_list![element_node! {
+                        tag: Token::Li,
+                        content: content_list_item![
+                            element_node! {
+                                tag: Token::P,
+                                content: content_inline_nodes![inline_text!("b")],
+                            },
+                        ],
+                    }],
+                },
+                element_node! {
+                    tag: Token::Hr,
+                    content: content_inline_nodes![],
+                },
+                element_node! {
+                    tag: Token::Hr,
+                    content: content_inline_nodes![],
+                },
+                element_node! {
+                    tag: Token::Blockquote,
+                    content: content_element_nodes![element_node! {
+                        tag: Token::Hr,
+                        content: content_inline_nodes![],
+                    }],
                 },
             ]);
-            let lex = Lexer::new(input);
-            assert_eq!(lex.parse(), expected);
+            assert_eq!(without_span(Lexer::new(input).parse()), expected);
+        }
+
+        #[test]
+        fn test_parse_deeply_nested_blocks() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: String,
+                expected: (usize, String),
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should parse list nested too deep as paragraph"),
+                    input: "- ".repeat(10000) + "a",
+                    expected: (
+                        MAX_NESTING_DEPTH,
+                        "- ".repeat(10000 - MAX_NESTING_DEPTH) + "a",
+                    ),
+                },
+                TestCase {
+                    it: String::from("should parse ordered list nested too deep as paragraph"),
+                    input: "1. ".repeat(10000) + "a",
+                    expected: (
+                        MAX_NESTING_DEPTH,
+                        "1. ".repeat(10000 - MAX_NESTING_DEPTH) + "a",
+                    ),
+                },
+                TestCase {
+                    it: String::from("should count lists of both kinds for the depth"),
+                    input: "- 1. ".repeat(5000) + "a",
+                    expected: (
+                        MAX_NESTING_DEPTH,
+                        "- 1. ".repeat(5000 - MAX_NESTING_DEPTH / 2) + "a",
+                    ),
+                },
+                TestCase {
+                    it: String::from("should parse quote nested too deep as paragraph"),
+                    input: "> ".repeat(10000) + "a",
+                    expected: (
+                        MAX_NESTING_DEPTH,
+                        "> ".repeat(10000 - MAX_NESTING_DEPTH) + "a",
+                    ),
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let md_node = Lexer::new(vec![test_case.input.clone()]).parse();
+                let mut node = &md_node.element_nodes()[0];
+                let mut depth = 0;
+                let text = loop {
+                    let (tag, content) = match node {
+                        ElementNode::Exist { tag, content, .. } => (tag, content),
+                        ElementNode::Nil => unreachable!(),
+                    };
+                    if matches!(tag, Token::Ul | Token::Ol | Token::Blockquote) {
+                        depth += 1;
+                    }
+                    match &**content {
+                        Content::List { value, .. }
+                        | Content::ListItem { value, .. }
+                        | Content::ElementNodes { value } => node = &value[0],
+                        Content::InlineNodes { value } => break text_content(value),
+                        _ => unreachable!(),
+                    }
+                };
+                // the output is too long to print on failure
+                assert!(
+                    (depth, text) == test_case.expected,
+                    "Failed: {}\n",
+                    test_case.it
+                );
+            }
+        }
+
+        #[test]
+        fn test_parse_span() {
+            let input = vec_string!["# hello", "", "> quote", "> *text*", "", "```", "code", "```"];
+            let md_node = Lexer::new(input).parse();
+            let spans: Vec<(Position, Position)> = md_node
+                .element_nodes()
+                .iter()
+                .filter_map(|node| node.span().map(|span| (span.start, span.end)))
+                .collect();
+            assert_eq!(
+                spans,
+                vec![
+                    (Position::new(1, 1, 0), Position::new(1, 8, 7)),
+                    (Position::new(3, 1, 9), Position::new(4, 9, 25)),
+                    (Position::new(6, 1, 27), Position::new(8, 4, 39)),
+                ]
+            );
+            let quote = match &md_node.element_nodes()[1] {
+                ElementNode::Exist { content, .. } => match &**content {
+                    Content::ElementNodes { value } => value[0].clone(),
+                    _ => unreachable!(),
+                },
+                ElementNode::Nil => unreachable!(),
+            };
+            let (line_break, emphasis) = match &quote {
+                ElementNode::Exist { content, .. } => match &**content {
+                    Content::InlineNodes { value } => (value[1].clone(), value[2].clone()),
+                    _ => unreachable!(),
+                },
+                ElementNode::Nil => unreachable!(),
+            };
+            assert_eq!(
+                quote.span().map(|span| span.start),
+                Some(Position::new(3, 3, 11))
+            );
+            assert_eq!(
+                line_break,
+                InlineNode::SoftBreak {
+                    span: Span::new(Position::new(3, 8, 16), Position::new(4, 3, 19))
+                }
+            );
+            assert_eq!(emphasis.span().start, Position::new(4, 3, 19));
+            assert_eq!(emphasis.span().end, Position::new(4, 9, 25));
         }
     }
 }
@@ -1,5 +1,8 @@
 pub mod code_block;
+pub mod heading;
+pub mod html_block;
 pub mod inline;
 pub mod list;
 pub mod quote;
 pub mod table;
+pub mod thematic_break;
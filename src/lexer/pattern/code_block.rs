@@ -1,22 +1,92 @@
 pub mod code_block {
-    use crate::util::string::string::escape_code_string;
+    use crate::lexer::pattern::inline::inline::unescape;
+    use crate::lexer::source::source::Line;
     use once_cell::sync::Lazy;
     use regex::Regex;
 
-    static CODE_BLOCK_PAREN_PATTERN: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"^```[^`|.]*$").unwrap());
+    static FENCE_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^( {0,3})(`{3,}|~{3,})(.*)$").unwrap());
 
-    pub fn is_code_block_start(input: &String) -> bool {
-        CODE_BLOCK_PAREN_PATTERN.is_match(input)
+    /// Opening fence of a code block, such as ```` ```rust ````.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Fence {
+        /// `` ` `` or `~`.
+        pub marker: char,
+        /// Count of the markers. The closing fence must be as long as this.
+        pub length: usize,
+        /// Spaces before the fence, removed from each line of the code.
+        pub indent: usize,
+        /// Text after the markers, such as `rust title=main.rs`.
+        pub info: String,
     }
 
-    pub fn parse_code_block(input: Vec<String>) -> Vec<String> {
-        input.iter().map(|s| escape_code_string(s)).collect()
+    impl Fence {
+        /// First word of the info string.
+        pub fn language(&self) -> Option<String> {
+            self.info.split_whitespace().next().map(|s| s.to_string())
+        }
+
+        /// Rest of the info string after the language, such as `title=main.rs`.
+        pub fn meta(&self) -> Option<String> {
+            self.info
+                .trim()
+                .split_once(char::is_whitespace)
+                .map(|(_, meta)| meta.trim().to_string())
+                .filter(|meta| !meta.is_empty())
+        }
+
+        /// Whether `input` closes the code block opened by this fence.
+        pub fn is_closed_by(&self, input: &str) -> bool {
+            match parse_fence(input) {
+                Some(fence) => {
+                    fence.marker == self.marker
+                        && fence.length >= self.length
+                        && fence.info.trim().is_empty()
+                }
+                None => false,
+            }
+        }
+    }
+
+    /// Returns the fence if `input` opens a code block.
+    pub fn parse_fence(input: &str) -> Option<Fence> {
+        let caps = FENCE_PATTERN.captures(input)?;
+        let markers = &caps[2];
+        let info = &caps[3];
+        let marker = markers.chars().next()?;
+        // info string of backtick fence can not have backticks, or it is a code span
+        if marker == '`' && info.contains('`') {
+            return None;
+        }
+        Some(Fence {
+            marker,
+            length: markers.len(),
+            indent: caps[1].len(),
+            info: unescape(info.trim()),
+        })
+    }
+
+    pub fn is_code_block_start(input: &str) -> bool {
+        parse_fence(input).is_some()
+    }
+
+    /// Join lines of code, removing up to `indent` spaces of the fence from each line.
+    pub fn parse_code_block(input: &[Line], indent: usize) -> String {
+        input
+            .iter()
+            .map(|line| {
+                let spaces = line.text.len() - line.text.trim_start_matches(' ').len();
+                &line.text[spaces.min(indent)..]
+            })
+            .collect::<Vec<&str>>()
+            .join("\n")
     }
 
     #[cfg(test)]
     mod test_code_block {
         use super::*;
+        use crate::lexer::source::source::source_lines;
+        use crate::vec_string;
 
         #[test]
         fn test_is_code_block_start() {
@@ -47,6 +117,21 @@ pub mod code_block {
                     input: String::from("``"),
                     expected: false,
                 },
+                TestCase {
+                    it: String::from("should return true when input is ~~~ with indent"),
+                    input: String::from("   ~~~~ rust title=main.rs"),
+                    expected: true,
+                },
+                TestCase {
+                    it: String::from("should return false when indent is 4 spaces"),
+                    input: String::from("    ```"),
+                    expected: false,
+                },
+                TestCase {
+                    it: String::from("should return false when info has backticks"),
+                    input: String::from("``` a`b"),
+                    expected: false,
+                },
             ];
             for test_case in test_cases.iter() {
                 let output = is_code_block_start(&test_case.input);
@@ -56,7 +141,7 @@ pub mod code_block {
 
         #[test]
         fn test_parse_code_block() {
-            let input = [
+            let input: Vec<String> = [
                 r#"<script src="/a/b.js">alert('aaa')</script>"#,
                 r#"'aaa'"#,
                 r#""aaa""#,
@@ -64,16 +149,27 @@ pub mod code_block {
             .iter()
             .map(|&s| s.into())
             .collect();
-            let expected: Vec<String> = [
-                "&lt;script src=&quot;/a/b.js&quot;&gt;alert(&#39;aaa&#39;)&lt;/script&gt;",
-                "&#39;aaa&#39;",
-                "&quot;aaa&quot;",
-            ]
-            .iter()
-            .map(|&s| s.into())
-            .collect();
-            let output = parse_code_block(input);
+            let expected = r#"<script src="/a/b.js">alert('aaa')</script>
+'aaa'
+"aaa""#
+                .to_string();
+            let output = parse_code_block(&source_lines(input), 0);
             assert_eq!(output, expected);
         }
+
+        #[test]
+        fn test_fence() {
+            let fence = parse_fence("  ````rust  title=main.rs ").unwrap();
+            assert_eq!(fence.language(), Some("rust".to_string()));
+            assert_eq!(fence.meta(), Some("title=main.rs".to_string()));
+            assert_eq!(fence.indent, 2);
+            assert!(!fence.is_closed_by("```"));
+            assert!(!fence.is_closed_by("~~~~"));
+            assert!(!fence.is_closed_by("````` rust"));
+            assert!(fence.is_closed_by("   `````  "));
+
+            let output = parse_code_block(&source_lines(vec_string![" a", "   b", "c"]), 2);
+            assert_eq!(output, "a\n b\nc");
+        }
     }
 }
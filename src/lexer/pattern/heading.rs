@@ -0,0 +1,114 @@
+pub mod heading {
+    use crate::lexer::lexer::lexer::Token;
+    use crate::lexer::source::source::Line;
+    use once_cell::sync::Lazy;
+    use regex::Regex;
+
+    static ATX_HEADING_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^ {0,3}(#{1,6})(?:[ \t]+|$)").unwrap());
+
+    static CLOSING_SEQUENCE_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?:^|[ \t]+)#+[ \t]*$").unwrap());
+
+    static SETEXT_UNDERLINE_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^ {0,3}(?:=+|-+)[ \t]*$").unwrap());
+
+    /// Whether `input` is `#` to `######` followed by a space or the end of the line.
+    pub fn is_atx_heading(input: &str) -> bool {
+        ATX_HEADING_PATTERN.is_match(input)
+    }
+
+    /// Tag and content of atx heading such as `## title ##`, without the closing `#`.
+    pub fn parse_atx_heading(input: &Line) -> Option<(Token, Line)> {
+        let caps = ATX_HEADING_PATTERN.captures(&input.text)?;
+        let tag = Token::value_of(caps[1].len() as i8);
+        let content = input.skip(caps[0].len());
+        let end = CLOSING_SEQUENCE_PATTERN
+            .find(&content.text)
+            .map_or(content.text.len(), |m| m.start());
+        Some((tag, content.take(end)))
+    }
+
+    /// Tag of setext heading underlined by `input`, `H1` for `===` and `H2` for `---`.
+    pub fn setext_heading_tag(input: &str) -> Option<Token> {
+        if !SETEXT_UNDERLINE_PATTERN.is_match(input) {
+            return None;
+        }
+        match input.trim_start().starts_with('=') {
+            true => Some(Token::H1),
+            false => Some(Token::H2),
+        }
+    }
+
+    #[cfg(test)]
+    mod test_heading {
+        use super::*;
+        use crate::lexer::source::source::Position;
+
+        #[test]
+        fn test_parse_atx_heading() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: String,
+                expected: Option<(Token, String)>,
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should parse heading"),
+                    input: String::from("## title"),
+                    expected: Some((Token::H2, String::from("title"))),
+                },
+                TestCase {
+                    it: String::from("should parse heading with leading spaces"),
+                    input: String::from("   # title"),
+                    expected: Some((Token::H1, String::from("title"))),
+                },
+                TestCase {
+                    it: String::from("should remove closing sequence"),
+                    input: String::from("### title ###  "),
+                    expected: Some((Token::H3, String::from("title"))),
+                },
+                TestCase {
+                    it: String::from("should keep `#` not separated by space"),
+                    input: String::from("# C# or F\\#"),
+                    expected: Some((Token::H1, String::from("C# or F\\#"))),
+                },
+                TestCase {
+                    it: String::from("should parse empty heading"),
+                    input: String::from("## ##"),
+                    expected: Some((Token::H2, String::from(""))),
+                },
+                TestCase {
+                    it: String::from("should not parse `#` without space"),
+                    input: String::from("#hashtag"),
+                    expected: None,
+                },
+                TestCase {
+                    it: String::from("should not parse seven `#`"),
+                    input: String::from("####### title"),
+                    expected: None,
+                },
+                TestCase {
+                    it: String::from("should not parse four spaces of indent"),
+                    input: String::from("    # title"),
+                    expected: None,
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let output =
+                    parse_atx_heading(&Line::new(test_case.input.clone(), Position::new(1, 1, 0)))
+                        .map(|(tag, content)| (tag, content.text));
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+
+        #[test]
+        fn test_setext_heading_tag() {
+            assert_eq!(setext_heading_tag("==="), Some(Token::H1));
+            assert_eq!(setext_heading_tag("  -  "), Some(Token::H2));
+            assert_eq!(setext_heading_tag("= ="), None);
+            assert_eq!(setext_heading_tag("    ---"), None);
+        }
+    }
+}
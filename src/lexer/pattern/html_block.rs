@@ -0,0 +1,193 @@
+pub mod html_block {
+    use crate::lexer::pattern::inline::inline::is_html_tag;
+    use crate::lexer::source::source::Line;
+    use once_cell::sync::Lazy;
+    use regex::Regex;
+
+    static RAW_TEXT_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^<(?:script|pre|style|textarea)(?:[ \t>]|$)").unwrap());
+
+    static BLOCK_TAG_PATTERN: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"^</?(?:address|article|aside|base|basefont|blockquote|body|caption|center|col|colgroup|dd|details|dialog|dir|div|dl|dt|fieldset|figcaption|figure|footer|form|frame|frameset|h[1-6]|head|header|hr|html|iframe|legend|li|link|main|menu|menuitem|nav|noframes|ol|optgroup|option|p|param|search|section|summary|table|tbody|td|tfoot|th|thead|title|tr|track|ul)(?:[ \t>]|/>|$)",
+        )
+        .unwrap()
+    });
+
+    /// Closers of html block which starts with `script`, `pre`, `style` or `textarea`.
+    const RAW_TEXT_CLOSERS: [&str; 4] = ["</script>", "</pre>", "</style>", "</textarea>"];
+
+    /// Line which ends html block.
+    #[derive(Debug, PartialEq)]
+    enum HtmlBlockEnd {
+        /// Line containing any of the closers, which is a part of the block.
+        Closer(&'static [&'static str]),
+        /// Blank line, which is not a part of the block.
+        BlankLine,
+    }
+
+    /// End of the html block which `input` starts, and whether it can interrupt a paragraph.
+    fn html_block_start(input: &str) -> Option<(HtmlBlockEnd, bool)> {
+        let trimmed = input.trim_start_matches(' ');
+        if input.len() - trimmed.len() > 3 || !trimmed.starts_with('<') {
+            return None;
+        }
+        let lower = trimmed.to_ascii_lowercase();
+        if RAW_TEXT_PATTERN.is_match(&lower) {
+            Some((HtmlBlockEnd::Closer(&RAW_TEXT_CLOSERS), true))
+        } else if lower.starts_with("<!--") {
+            Some((HtmlBlockEnd::Closer(&["-->"]), true))
+        } else if lower.starts_with("<?") {
+            Some((HtmlBlockEnd::Closer(&["?>"]), true))
+        } else if trimmed.starts_with("<![CDATA[") {
+            Some((HtmlBlockEnd::Closer(&["]]>"]), true))
+        } else if lower.starts_with("<!")
+            && lower[2..].starts_with(|c: char| c.is_ascii_alphabetic())
+        {
+            Some((HtmlBlockEnd::Closer(&[">"]), true))
+        } else if BLOCK_TAG_PATTERN.is_match(&lower) {
+            Some((HtmlBlockEnd::BlankLine, true))
+        } else if is_html_tag(trimmed.trim_end()) {
+            Some((HtmlBlockEnd::BlankLine, false))
+        } else {
+            None
+        }
+    }
+
+    /// Whether `input` starts html block, such as `<div>` or `<!-- comment`.
+    pub fn is_html_block_start(input: &str) -> bool {
+        html_block_start(input).is_some()
+    }
+
+    /// Whether `input` starts html block even in the middle of a paragraph.
+    ///
+    /// A line of any other tag, such as `<span>`, is a part of the paragraph.
+    pub fn interrupts_paragraph(input: &str) -> bool {
+        matches!(html_block_start(input), Some((_, true)))
+    }
+
+    /// Count of the lines of the html block at the head of `input`.
+    pub fn html_block_len(input: &[Line]) -> usize {
+        let end = match html_block_start(&input[0].text) {
+            Some((end, _)) => end,
+            None => return 0,
+        };
+        let position = input.iter().position(|line| match end {
+            HtmlBlockEnd::Closer(closers) => {
+                let lower = line.text.to_ascii_lowercase();
+                closers.iter().any(|closer| lower.contains(closer))
+            }
+            HtmlBlockEnd::BlankLine => line.text.trim().is_empty(),
+        });
+        match (position, end) {
+            (Some(i), HtmlBlockEnd::Closer(_)) => i + 1,
+            (Some(i), HtmlBlockEnd::BlankLine) => i,
+            // not closed html block runs to the end of the document
+            (None, _) => input.len(),
+        }
+    }
+
+    #[cfg(test)]
+    mod test_html_block {
+        use super::*;
+        use crate::lexer::source::source::source_lines;
+        use crate::vec_string;
+
+        #[test]
+        fn test_is_html_block_start() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: String,
+                expected: (bool, bool),
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should start block with block tag"),
+                    input: String::from(r#"<div class="note">"#),
+                    expected: (true, true),
+                },
+                TestCase {
+                    it: String::from("should start block with closing block tag"),
+                    input: String::from("  </DIV>"),
+                    expected: (true, true),
+                },
+                TestCase {
+                    it: String::from("should start block with script"),
+                    input: String::from("<script>alert(1)</script>"),
+                    expected: (true, true),
+                },
+                TestCase {
+                    it: String::from("should start block with comment"),
+                    input: String::from("<!-- note"),
+                    expected: (true, true),
+                },
+                TestCase {
+                    it: String::from("should start block with a line of other tag"),
+                    input: String::from(r#"<span title="a *b* c">"#),
+                    expected: (true, false),
+                },
+                TestCase {
+                    it: String::from("should not start block with other tag followed by text"),
+                    input: String::from("<span>a</span>"),
+                    expected: (false, false),
+                },
+                TestCase {
+                    it: String::from(
+                        "should not start block with tag which has the name as prefix",
+                    ),
+                    input: String::from("<divx a"),
+                    expected: (false, false),
+                },
+                TestCase {
+                    it: String::from("should not start block with four spaces of indent"),
+                    input: String::from("    <div>"),
+                    expected: (false, false),
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let output = (
+                    is_html_block_start(&test_case.input),
+                    interrupts_paragraph(&test_case.input),
+                );
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+
+        #[test]
+        fn test_html_block_len() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: Vec<String>,
+                expected: usize,
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should end block tag before blank line"),
+                    input: vec_string!["<div>", "*a*", "", "</div>"],
+                    expected: 2,
+                },
+                TestCase {
+                    it: String::from("should end script at the line of its closer"),
+                    input: vec_string!["<script>", "", "a()", "</SCRIPT> b", "c"],
+                    expected: 4,
+                },
+                TestCase {
+                    it: String::from("should end comment on the same line"),
+                    input: vec_string!["<!-- a -->", "b"],
+                    expected: 1,
+                },
+                TestCase {
+                    it: String::from("should run to the end without closer"),
+                    input: vec_string!["<!--", "a"],
+                    expected: 2,
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let output = html_block_len(&source_lines(test_case.input.clone()));
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+    }
+}
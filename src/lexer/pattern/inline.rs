@@ -1,126 +1,1159 @@
 pub mod inline {
+    use crate::lexer::lexer::lexer::InlineNode;
+    use crate::lexer::source::source::{Line, Position, Span};
     use once_cell::sync::Lazy;
     use regex::Regex;
+    use std::collections::HashMap;
 
-    pub struct Pattern {
-        pattern: Regex,
-        template: String,
+    static OPEN_TAG_PATTERN: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r#"^<[A-Za-z][A-Za-z0-9-]*(?:\s+[A-Za-z_:][A-Za-z0-9_.:-]*(?:\s*=\s*(?:[^\s"'=<>`]+|'[^']*'|"[^"]*"))?)*\s*/?>"#,
+        )
+        .unwrap()
+    });
+
+    static CLOSING_TAG_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^</[A-Za-z][A-Za-z0-9-]*\s*>").unwrap());
+
+    /// Openers of raw html which runs until its closer, such as comment.
+    const HTML_SECTIONS: [(&str, &str); 3] = [("<!--", "-->"), ("<?", "?>"), ("<![CDATA[", "]]>")];
+
+    /// Run of `*` or `_` which may open or close emphasis.
+    #[derive(Debug)]
+    struct Delimiter {
+        marker: char,
+        /// Characters left in the run.
+        count: usize,
+        /// Length of the whole run, used by the rule of 3.
+        length: usize,
+        /// Byte offset of the first character left in the run.
+        start: usize,
+        can_open: bool,
+        can_close: bool,
+    }
+
+    #[derive(Debug)]
+    enum Item {
+        Node(InlineNode),
+        Delimiter(Delimiter),
+    }
+
+    /// `[` or `![` waiting for its `]`.
+    #[derive(Debug)]
+    struct Bracket {
+        /// Index of the item of the bracket text.
+        index: usize,
+        /// Byte offset of the bracket text.
+        start: usize,
+        image: bool,
+        /// Brackets before a link are inactive, since links can not contain links.
+        active: bool,
     }
 
-    impl Pattern {
-        pub fn new(pattern: Regex, template: String) -> Self {
-            Pattern { pattern, template }
+    /// Inline parser following CommonMark, with its delimiter stack.
+    struct Parser<'a> {
+        text: &'a str,
+        /// Source position of every byte of `text` and of its end.
+        origin: Vec<Position>,
+        items: Vec<Item>,
+        brackets: Vec<Bracket>,
+        /// Closers of raw html not found in the rest of `text`, so that they are searched once.
+        missing_html_closers: Vec<&'static str>,
+    }
+
+    impl<'a> Parser<'a> {
+        fn new(text: &'a str, origin: Vec<Position>) -> Self {
+            Parser {
+                text,
+                origin,
+                items: vec![],
+                brackets: vec![],
+                missing_html_closers: vec![],
+            }
+        }
+
+        fn span(&self, start: usize, end: usize) -> Span {
+            Span::new(self.origin[start], self.origin[end])
+        }
+
+        fn push_text(&mut self, start: usize, end: usize) {
+            self.push_value(&self.text[start..end], start, end);
+        }
+
+        /// Push text `value` standing for the source from `start` to `end`.
+        fn push_value(&mut self, value: &str, start: usize, end: usize) {
+            self.items.push(Item::Node(InlineNode::Text {
+                value: value.to_string(),
+                span: self.span(start, end),
+            }));
+        }
+
+        fn parse(mut self) -> Vec<InlineNode> {
+            let mut i = 0;
+            let mut text_start = 0;
+            while let Some(c) = self.text[i..].chars().next() {
+                let next = match c {
+                    '\\' | '`' | '*' | '_' | '[' | ']' | '!' | '<' | '\n' => {
+                        // spaces before line ending are a part of the break
+                        let end = match c {
+                            '\n' => {
+                                text_start + self.text[text_start..i].trim_end_matches(' ').len()
+                            }
+                            _ => i,
+                        };
+                        self.push_text(text_start, end);
+                        let next = match c {
+                            '\n' => self.parse_line_break(i),
+                            '\\' => self.parse_escape(i),
+                            '`' => self.parse_code_span(i),
+                            '<' => self.parse_html(i),
+                            '*' | '_' => self.parse_delimiter(i, c),
+                            '[' => self.parse_open_bracket(i, false),
+                            '!' if self.text[i + 1..].starts_with('[') => {
+                                self.parse_open_bracket(i, true)
+                            }
+                            ']' => self.parse_close_bracket(i),
+                            _ => {
+                                self.push_text(i, i + 1);
+                                i + 1
+                            }
+                        };
+                        text_start = next;
+                        next
+                    }
+                    _ => i + c.len_utf8(),
+                };
+                i = next;
+            }
+            let end = self.text.trim_end_matches(' ').len().max(text_start);
+            self.push_text(text_start, end);
+            self.finish(0)
+        }
+
+        /// Line ending at `at` becomes hard break after two spaces, or soft break.
+        fn parse_line_break(&mut self, at: usize) -> usize {
+            let spaces = self.text[..at].len() - self.text[..at].trim_end_matches(' ').len();
+            let span = self.span(at - spaces, at + 1);
+            self.items.push(Item::Node(match spaces >= 2 {
+                true => InlineNode::HardBreak { span },
+                false => InlineNode::SoftBreak { span },
+            }));
+            self.skip_indent(at + 1)
+        }
+
+        /// Leading spaces of a continuation line are not a part of the text.
+        fn skip_indent(&self, at: usize) -> usize {
+            at + self.text[at..].len() - self.text[at..].trim_start_matches([' ', '\t']).len()
+        }
+
+        fn parse_escape(&mut self, at: usize) -> usize {
+            if self.text[at + 1..].starts_with('\n') {
+                let span = self.span(at, at + 2);
+                self.items.push(Item::Node(InlineNode::HardBreak { span }));
+                return self.skip_indent(at + 2);
+            }
+            match self.text[at + 1..].chars().next().filter(is_escapable) {
+                Some(escaped) => {
+                    let end = at + 1 + escaped.len_utf8();
+                    self.push_value(&escaped.to_string(), at, end);
+                    end
+                }
+                None => {
+                    self.push_text(at, at + 1);
+                    at + 1
+                }
+            }
+        }
+
+        /// Code span takes precedence over any other markup, and has no escapes.
+        fn parse_code_span(&mut self, at: usize) -> usize {
+            let fence = backtick_count(&self.text[at..]);
+            match find_closing_backticks(self.text, at + fence, fence) {
+                Some(close) => {
+                    let end = close + fence;
+                    self.items.push(Item::Node(InlineNode::Code {
+                        value: code_span_content(&self.text[at + fence..close]),
+                        span: self.span(at, end),
+                    }));
+                    end
+                }
+                None => {
+                    // unmatched backticks are text as they are
+                    self.push_text(at, at + fence);
+                    at + fence
+                }
+            }
+        }
+
+        /// Raw html is taken as a whole, so markup in its attributes is not parsed.
+        fn parse_html(&mut self, at: usize) -> usize {
+            match self.html_len(at) {
+                Some(len) => {
+                    self.items.push(Item::Node(InlineNode::Html {
+                        value: self.text[at..at + len].to_string(),
+                        span: self.span(at, at + len),
+                    }));
+                    at + len
+                }
+                None => {
+                    self.push_text(at, at + 1);
+                    at + 1
+                }
+            }
+        }
+
+        /// Length of the raw html at `at`, which is a tag, comment,
+        /// processing instruction, declaration or CDATA section.
+        fn html_len(&mut self, at: usize) -> Option<usize> {
+            let rest = &self.text[at..];
+            // empty comments
+            if rest.starts_with("<!-->") {
+                return Some(5);
+            }
+            if rest.starts_with("<!--->") {
+                return Some(6);
+            }
+            if let Some((open, close)) = HTML_SECTIONS
+                .iter()
+                .find(|(open, _)| rest.starts_with(open))
+            {
+                return self
+                    .find_html_closer(at + open.len(), close)
+                    .map(|end| end - at);
+            }
+            if rest.starts_with("<!") && rest[2..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+                return self.find_html_closer(at + 2, ">").map(|end| end - at);
+            }
+            OPEN_TAG_PATTERN
+                .find(rest)
+                .or_else(|| CLOSING_TAG_PATTERN.find(rest))
+                .map(|m| m.end())
+        }
+
+        /// End of the first `close` from `from`.
+        fn find_html_closer(&mut self, from: usize, close: &'static str) -> Option<usize> {
+            if self.missing_html_closers.contains(&close) {
+                return None;
+            }
+            match self.text[from..].find(close) {
+                Some(at) => Some(from + at + close.len()),
+                None => {
+                    self.missing_html_closers.push(close);
+                    None
+                }
+            }
         }
 
-        pub fn parse(&self, input: &String) -> String {
-            self.pattern.replace_all(input, &self.template).to_string()
+        fn parse_delimiter(&mut self, at: usize, marker: char) -> usize {
+            let length = self.text[at..].chars().take_while(|c| *c == marker).count();
+            let end = at + length;
+            let before = self.text[..at].chars().next_back();
+            let after = self.text[end..].chars().next();
+            let left_flanking = is_left_flanking(before, after);
+            let right_flanking = is_left_flanking(after, before);
+            // `_` does not make emphasis inside words, such as `snake_case_name`
+            let (can_open, can_close) = if marker == '*' {
+                (left_flanking, right_flanking)
+            } else {
+                (
+                    left_flanking && (!right_flanking || before.is_some_and(is_punctuation)),
+                    right_flanking && (!left_flanking || after.is_some_and(is_punctuation)),
+                )
+            };
+            self.items.push(Item::Delimiter(Delimiter {
+                marker,
+                count: length,
+                length,
+                start: at,
+                can_open,
+                can_close,
+            }));
+            end
+        }
+
+        fn parse_open_bracket(&mut self, at: usize, image: bool) -> usize {
+            let end = if image { at + 2 } else { at + 1 };
+            self.brackets.push(Bracket {
+                index: self.items.len(),
+                start: at,
+                image,
+                active: true,
+            });
+            self.push_text(at, end);
+            end
+        }
+
+        fn parse_close_bracket(&mut self, at: usize) -> usize {
+            let bracket = match self.brackets.pop() {
+                Some(bracket) => bracket,
+                None => {
+                    self.push_text(at, at + 1);
+                    return at + 1;
+                }
+            };
+            let destination = match bracket.active {
+                true => parse_destination(self.text, at + 1),
+                false => None,
+            };
+            let (url, title, end) = match destination {
+                Some(destination) => destination,
+                None => {
+                    self.push_text(at, at + 1);
+                    return at + 1;
+                }
+            };
+            let children = self.finish(bracket.index + 1);
+            self.items.truncate(bracket.index);
+            let span = self.span(bracket.start, end);
+            let node = if bracket.image {
+                InlineNode::Image {
+                    src: url,
+                    alt: text_content(&children),
+                    title,
+                    span,
+                }
+            } else {
+                for bracket in self.brackets.iter_mut().filter(|bracket| !bracket.image) {
+                    bracket.active = false;
+                }
+                InlineNode::Link {
+                    href: url,
+                    title,
+                    children,
+                    span,
+                }
+            };
+            self.items.push(Item::Node(node));
+            end
+        }
+
+        /// Match delimiters in `items` into emphasis and strong.
+        fn process_emphasis(&self, items: Vec<Item>) -> Vec<Item> {
+            let mut list = ItemList::new(items);
+            // an opener is searched only above the bottom of the closer's kind, so that
+            // a document of many closers without opener is processed in linear time
+            let mut openers_bottom: HashMap<(char, bool, usize), usize> = HashMap::new();
+            let mut closer = list.first();
+            while let Some(current) = closer {
+                let (marker, closer_length, closer_can_open) = match list.delimiter(current) {
+                    Some(d) if d.can_close => (d.marker, d.length, d.can_open),
+                    _ => {
+                        closer = list.next[current];
+                        continue;
+                    }
+                };
+                let kind = (marker, closer_can_open, closer_length % 3);
+                let bottom = openers_bottom.get(&kind).copied();
+                let above_bottom = |i: &usize| bottom.is_none_or(|bottom| *i > bottom);
+                let mut opener = list.prev[current].filter(above_bottom);
+                while let Some(i) = opener {
+                    let found = list.delimiter(i).is_some_and(|d| {
+                        d.marker == marker
+                            && d.can_open
+                            // rule of 3, so `*a**b*` is not `<em>a</em><em>b</em>`
+                            && !((d.can_close || closer_can_open)
+                                && (d.length + closer_length) % 3 == 0
+                                && !(d.length % 3 == 0 && closer_length % 3 == 0))
+                    });
+                    if found {
+                        break;
+                    }
+                    opener = list.prev[i].filter(above_bottom);
+                }
+                match opener {
+                    Some(opener) => closer = self.match_delimiters(&mut list, opener, current),
+                    None => {
+                        if let Some(prev) = list.prev[current] {
+                            openers_bottom.insert(kind, prev);
+                        }
+                        if let Some(item) =
+                            list.items[current].as_mut().filter(|_| !closer_can_open)
+                        {
+                            self.delimiter_to_text(item);
+                        }
+                        closer = list.next[current];
+                    }
+                }
+            }
+            list.items
+                .into_iter()
+                .flatten()
+                .map(|mut item| {
+                    self.delimiter_to_text(&mut item);
+                    item
+                })
+                .collect()
+        }
+
+        /// Wrap items between `opener` and `closer` into a node.
+        ///
+        /// Returns index of the closer to look at next.
+        fn match_delimiters(
+            &self,
+            list: &mut ItemList,
+            opener: usize,
+            closer: usize,
+        ) -> Option<usize> {
+            let (opener_count, opener_start) = match list.delimiter(opener) {
+                Some(d) => (d.count, d.start),
+                None => unreachable!(),
+            };
+            let closer_count = match list.delimiter(closer) {
+                Some(d) => d.count,
+                None => unreachable!(),
+            };
+            let used = if opener_count >= 2 && closer_count >= 2 {
+                2
+            } else {
+                1
+            };
+            let mut children: Vec<Item> = vec![];
+            let mut child = list.next[opener];
+            while let Some(i) = child.filter(|i| *i != closer) {
+                let mut item = list.items[i].take();
+                if let Some(item) = &mut item {
+                    self.delimiter_to_text(item);
+                }
+                children.extend(item);
+                child = list.next[i];
+            }
+            let start = opener_start + opener_count - used;
+            let end = match list.delimiter_mut(closer) {
+                Some(d) => {
+                    d.count -= used;
+                    d.start += used;
+                    d.start
+                }
+                None => unreachable!(),
+            };
+            let children = merge_text(children);
+            let span = self.span(start, end);
+            let node = if used == 2 {
+                InlineNode::Strong { children, span }
+            } else {
+                InlineNode::Emphasis { children, span }
+            };
+            // the node takes the place of its first child, which keeps the items in order
+            let node_index = list.next[opener].unwrap_or(closer);
+            list.items[node_index] = Some(Item::Node(node));
+            list.link(opener, node_index);
+            list.link(node_index, closer);
+            if let Some(d) = list.delimiter_mut(opener) {
+                d.count -= used;
+                if d.count == 0 {
+                    list.remove(opener);
+                }
+            }
+            match list.delimiter(closer) {
+                Some(d) if d.count == 0 => {
+                    let next = list.next[closer];
+                    list.remove(closer);
+                    next
+                }
+                _ => Some(closer),
+            }
+        }
+
+        /// Turn delimiter `item` into text of the characters left in it.
+        fn delimiter_to_text(&self, item: &mut Item) {
+            if let Item::Delimiter(d) = item {
+                let value = d.marker.to_string().repeat(d.count);
+                let span = self.span(d.start, d.start + d.count);
+                *item = Item::Node(InlineNode::Text { value, span });
+            }
+        }
+
+        /// Take the items from `bottom` as nodes.
+        fn finish(&mut self, bottom: usize) -> Vec<InlineNode> {
+            let items = self.items.drain(bottom..).collect();
+            merge_text(self.process_emphasis(items))
         }
     }
 
-    static IMAGE_PARSE: Lazy<Pattern> = Lazy::new(|| {
-        Pattern::new(
-            Regex::new(r"!\[(.*?)]\((.*?)\)").unwrap(),
-            r#"<img class="flav-md-img" src="$2" alt="$1">"#.to_string(),
-        )
-    });
+    /// Items linked to each other, so that matched items are taken out
+    /// without shifting the rest.
+    ///
+    /// Items left are always in the order of their indices.
+    struct ItemList {
+        items: Vec<Option<Item>>,
+        prev: Vec<Option<usize>>,
+        next: Vec<Option<usize>>,
+    }
 
-    static LINK_PARSE: Lazy<Pattern> = Lazy::new(|| {
-        Pattern::new(
-            Regex::new(r"\[(.*?)]\((.*?)\)").unwrap(),
-            r#"<a class="flav-md-a" href="$2" alt="$1">$1</a>"#.to_string(),
-        )
-    });
+    impl ItemList {
+        fn new(items: Vec<Item>) -> Self {
+            let len = items.len();
+            ItemList {
+                items: items.into_iter().map(Some).collect(),
+                prev: (0..len).map(|i| i.checked_sub(1)).collect(),
+                next: (0..len)
+                    .map(|i| Some(i + 1).filter(|next| *next < len))
+                    .collect(),
+            }
+        }
 
-    static CODE_PARSE: Lazy<Pattern> = Lazy::new(|| {
-        Pattern::new(
-            Regex::new(r"`(.*?)`").unwrap(),
-            r#"<code class="flav-md-code-inline">$1</code>"#.to_string(),
-        )
-    });
+        fn first(&self) -> Option<usize> {
+            self.items.iter().position(Option::is_some)
+        }
 
-    static STRONG_PARSE: Lazy<Pattern> = Lazy::new(|| {
-        Pattern::new(
-            Regex::new(r"\*{2}(.*?)\*{2}").unwrap(),
-            r#"<strong class="flav-md-strong">$1</strong>"#.to_string(),
-        )
-    });
+        fn delimiter(&self, index: usize) -> Option<&Delimiter> {
+            match &self.items[index] {
+                Some(Item::Delimiter(d)) => Some(d),
+                _ => None,
+            }
+        }
 
-    static EMPHASIS_PARSE: Lazy<Pattern> = Lazy::new(|| {
-        Pattern::new(
-            Regex::new(r"\*(.*?)\*").unwrap(),
-            r#"<em class="flav-md-em">$1</em>"#.to_string(),
-        )
-    });
+        fn delimiter_mut(&mut self, index: usize) -> Option<&mut Delimiter> {
+            match &mut self.items[index] {
+                Some(Item::Delimiter(d)) => Some(d),
+                _ => None,
+            }
+        }
+
+        fn link(&mut self, prev: usize, next: usize) {
+            self.next[prev] = Some(next);
+            self.prev[next] = Some(prev);
+        }
+
+        fn remove(&mut self, index: usize) {
+            self.items[index] = None;
+            let (prev, next) = (self.prev[index], self.next[index]);
+            if let Some(prev) = prev {
+                self.next[prev] = next;
+            }
+            if let Some(next) = next {
+                self.prev[next] = prev;
+            }
+        }
+    }
 
-    trait Parsable {
-        fn parse(&self, parser: &Pattern) -> Self;
+    /// Nodes of `items`, joining adjacent text and dropping empty text.
+    fn merge_text(items: Vec<Item>) -> Vec<InlineNode> {
+        let mut rslt: Vec<InlineNode> = vec![];
+        for item in items {
+            let node = match item {
+                Item::Node(node) => node,
+                Item::Delimiter(_) => unreachable!("delimiters are processed before"),
+            };
+            match (rslt.last_mut(), node) {
+                (_, InlineNode::Text { value, .. }) if value.is_empty() => {}
+                (
+                    Some(InlineNode::Text {
+                        value: last,
+                        span: last_span,
+                    }),
+                    InlineNode::Text { value, span },
+                ) => {
+                    last.push_str(&value);
+                    last_span.end = span.end;
+                }
+                (_, node) => rslt.push(node),
+            }
+        }
+        rslt
+    }
+
+    fn is_punctuation(c: char) -> bool {
+        c.is_ascii_punctuation() || !(c.is_alphanumeric() || c.is_whitespace() || c.is_control())
     }
 
-    impl Parsable for String {
-        fn parse(&self, parser: &Pattern) -> Self {
-            parser.parse(self)
+    /// Whether a delimiter run between `before` and `after` is left-flanking.
+    ///
+    /// Swapping them tells whether it is right-flanking.
+    /// `None` is the start or end of the line, which counts as whitespace.
+    fn is_left_flanking(before: Option<char>, after: Option<char>) -> bool {
+        let before_is_space = before.is_none_or(char::is_whitespace);
+        match after {
+            Some(after) if !after.is_whitespace() => {
+                !is_punctuation(after) || before_is_space || before.is_some_and(is_punctuation)
+            }
+            _ => false,
         }
     }
 
-    pub fn inline_parse(input: &String) -> String {
-        input
-            .parse(&IMAGE_PARSE)
-            .parse(&LINK_PARSE)
-            .parse(&CODE_PARSE)
-            .parse(&STRONG_PARSE)
-            .parse(&EMPHASIS_PARSE)
+    /// Nesting of parentheses allowed in the url of a link destination.
+    const MAX_DESTINATION_PARENS: usize = 32;
+
+    /// Parse `(url "title")` of inline link from byte `at` of `text`.
+    ///
+    /// Returns url, title and the end of the destination.
+    fn parse_destination(text: &str, at: usize) -> Option<(String, Option<String>, usize)> {
+        let rest = text[at..].strip_prefix('(')?;
+        let mut i = at + 1 + (rest.len() - rest.trim_start().len());
+        let url_start = i;
+        let url = if text[i..].starts_with('<') {
+            let close = find_unescaped(text, i + 1, |c| c == '>' || c == '<')?;
+            if !text[close..].starts_with('>') {
+                return None;
+            }
+            i = close + 1;
+            &text[url_start + 1..close]
+        } else {
+            let mut depth = 0;
+            let mut chars = text[i..].char_indices();
+            let mut end = text.len();
+            while let Some((offset, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    // deeper parentheses are not a destination, which bounds the scan
+                    '(' if depth == MAX_DESTINATION_PARENS => return None,
+                    '(' => depth += 1,
+                    ')' if depth == 0 => {
+                        end = i + offset;
+                        break;
+                    }
+                    ')' => depth -= 1,
+                    c if c.is_whitespace() || c.is_control() => {
+                        end = i + offset;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            i = end;
+            &text[url_start..end]
+        };
+        let spaces = text[i..].len() - text[i..].trim_start().len();
+        let title = match text[i + spaces..].chars().next() {
+            Some(open @ ('"' | '\'' | '(')) if spaces > 0 => {
+                let close = if open == '(' { ')' } else { open };
+                let title_start = i + spaces + 1;
+                // title in parentheses can not contain `(`
+                let title_end = find_unescaped(text, title_start, |c| c == close || c == open)?;
+                if !text[title_end..].starts_with(close) {
+                    return None;
+                }
+                i = title_end + 1;
+                Some(unescape(&text[title_start..title_end]))
+            }
+            _ => None,
+        };
+        let spaces = text[i..].len() - text[i..].trim_start().len();
+        if !text[i + spaces..].starts_with(')') {
+            return None;
+        }
+        Some((unescape(url), title, i + spaces + 1))
+    }
+
+    /// Byte offset of the first character from `from` matching `pattern` and not escaped.
+    fn find_unescaped(text: &str, from: usize, pattern: impl Fn(char) -> bool) -> Option<usize> {
+        let mut chars = text[from..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            if c == '\\' {
+                chars.next();
+            } else if pattern(c) {
+                return Some(from + offset);
+            }
+        }
+        None
+    }
+
+    /// Concatenated text of `nodes`, dropping any markup.
+    pub fn text_content(nodes: &[InlineNode]) -> String {
+        nodes
+            .iter()
+            .map(|node| match node {
+                InlineNode::Text { value, .. }
+                | InlineNode::Code { value, .. }
+                | InlineNode::Html { value, .. } => value.to_string(),
+                InlineNode::Emphasis { children, .. }
+                | InlineNode::Strong { children, .. }
+                | InlineNode::Link { children, .. } => text_content(children),
+                InlineNode::Image { alt, .. } => alt.to_string(),
+                InlineNode::SoftBreak { .. } | InlineNode::HardBreak { .. } => "\n".to_string(),
+            })
+            .collect()
+    }
+
+    /// Whether the whole `input` is one open or closing tag.
+    pub fn is_html_tag(input: &str) -> bool {
+        [&OPEN_TAG_PATTERN, &CLOSING_TAG_PATTERN]
+            .iter()
+            .any(|pattern| pattern.find(input).is_some_and(|m| m.end() == input.len()))
+    }
+
+    /// Whether `c` loses its meaning as markup when it follows a backslash.
+    pub fn is_escapable(c: &char) -> bool {
+        c.is_ascii_punctuation()
+    }
+
+    /// Remove backslashes of escaped characters in `input`.
+    pub fn unescape(input: &str) -> String {
+        let mut rslt = String::with_capacity(input.len());
+        let mut chars = input.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                if let Some(escaped) = chars.next_if(is_escapable) {
+                    rslt.push(escaped);
+                    continue;
+                }
+            }
+            rslt.push(c);
+        }
+        rslt
+    }
+
+    fn backtick_count(text: &str) -> usize {
+        text.chars().take_while(|c| *c == '`').count()
+    }
+
+    /// Start of the backtick string of length `fence` closing a code span from `from`.
+    fn find_closing_backticks(text: &str, from: usize, fence: usize) -> Option<usize> {
+        let mut i = from;
+        while let Some(at) = text[i..].find('`') {
+            let start = i + at;
+            let count = backtick_count(&text[start..]);
+            if count == fence {
+                return Some(start);
+            }
+            i = start + count;
+        }
+        None
+    }
+
+    /// Strip one space on both sides of code span `content` unless it is only spaces.
+    ///
+    /// Line endings in it are spaces.
+    fn code_span_content(content: &str) -> String {
+        let content = &content.replace('\n', " ");
+        if content.len() > 1
+            && content.starts_with(' ')
+            && content.ends_with(' ')
+            && !content.chars().all(|c| c == ' ')
+        {
+            content[1..content.len() - 1].to_string()
+        } else {
+            content.to_string()
+        }
+    }
+
+    /// Source position of every byte of `input` and of its end.
+    ///
+    /// For a line followed by another line, the end is where its line ending is.
+    fn origin_of(input: &Line) -> Vec<Position> {
+        let mut origin: Vec<Position> = Vec::with_capacity(input.text.len() + 1);
+        let mut position = input.start;
+        for c in input.text.chars() {
+            origin.extend(std::iter::repeat_n(position, c.len_utf8()));
+            position = position.advance(c.encode_utf8(&mut [0; 4]));
+        }
+        origin.push(position);
+        origin
+    }
+
+    /// Parse `input` as one inline content, where the line endings are breaks.
+    pub fn inline_parse_lines(input: &[Line]) -> Vec<InlineNode> {
+        let text = input
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n");
+        let origin = input.iter().flat_map(origin_of).collect();
+        Parser::new(&text, origin).parse()
     }
 
     #[cfg(test)]
     mod test_inline {
         use super::*;
+        use crate::inline_text;
+
+        fn parse(input: &str) -> Vec<InlineNode> {
+            inline_parse_lines(&[Line::new(input.to_string(), Position::new(1, 1, 0))])
+                .into_iter()
+                .map(InlineNode::without_span)
+                .collect()
+        }
 
         #[test]
         fn test_image_pattern() {
-            let output = inline_parse(&String::from("![hoge1](hoge2)"));
+            let output = parse("![hoge1](hoge2)");
             assert_eq!(
                 output,
-                String::from(r#"<img class="flav-md-img" src="hoge2" alt="hoge1">"#)
+                vec![InlineNode::Image {
+                    src: "hoge2".to_string(),
+                    alt: "hoge1".to_string(),
+                    title: None,
+                    span: Default::default()
+                }]
             );
         }
 
         #[test]
         fn test_link_pattern() {
-            let output = inline_parse(&String::from("[hoge1](hoge2)"));
+            let output = parse("[hoge1](hoge2)");
             assert_eq!(
                 output,
-                String::from(r#"<a class="flav-md-a" href="hoge2" alt="hoge1">hoge1</a>"#)
+                vec![InlineNode::Link {
+                    href: "hoge2".to_string(),
+                    title: None,
+                    children: vec![inline_text!("hoge1")],
+                    span: Default::default()
+                }]
+            );
+        }
+
+        #[test]
+        fn test_link_pattern_with_title() {
+            let output = parse(r#"[hoge1](hoge2 "fuga")"#);
+            assert_eq!(
+                output,
+                vec![InlineNode::Link {
+                    href: "hoge2".to_string(),
+                    title: Some("fuga".to_string()),
+                    children: vec![inline_text!("hoge1")],
+                    span: Default::default()
+                }]
             );
         }
 
         #[test]
         fn test_code_pattern() {
-            let output = inline_parse(&String::from("`hoge`"));
+            let output = parse("`hoge`");
             assert_eq!(
                 output,
-                String::from(r#"<code class="flav-md-code-inline">hoge</code>"#)
+                vec![InlineNode::Code {
+                    value: "hoge".to_string(),
+                    span: Default::default()
+                }]
             );
         }
 
         #[test]
         fn test_strong_pattern() {
-            let output = inline_parse(&String::from("**hoge**"));
+            let output = parse("**hoge**");
             assert_eq!(
                 output,
-                String::from(r#"<strong class="flav-md-strong">hoge</strong>"#)
+                vec![InlineNode::Strong {
+                    children: vec![inline_text!("hoge")],
+                    span: Default::default()
+                }]
             );
         }
 
         #[test]
         fn test_emphasis_pattern() {
-            let output = inline_parse(&String::from("*hoge*"));
-            assert_eq!(output, String::from(r#"<em class="flav-md-em">hoge</em>"#));
+            let output = parse("*hoge*");
+            assert_eq!(
+                output,
+                vec![InlineNode::Emphasis {
+                    children: vec![inline_text!("hoge")],
+                    span: Default::default()
+                }]
+            );
         }
 
         #[test]
         fn test_inline_parse() {
-            let output = inline_parse(&String::from("*hoge*"));
-            assert_eq!(output, String::from(r#"<em class="flav-md-em">hoge</em>"#));
+            let output = parse("this is *hoge **fuga*** and [**piyo**](url)");
+            assert_eq!(
+                output,
+                vec![
+                    inline_text!("this is "),
+                    InlineNode::Emphasis {
+                        children: vec![
+                            inline_text!("hoge "),
+                            InlineNode::Strong {
+                                children: vec![inline_text!("fuga")],
+                                span: Default::default()
+                            },
+                        ],
+                        span: Default::default()
+                    },
+                    inline_text!(" and "),
+                    InlineNode::Link {
+                        href: "url".to_string(),
+                        title: None,
+                        children: vec![InlineNode::Strong {
+                            children: vec![inline_text!("piyo")],
+                            span: Default::default()
+                        }],
+                        span: Default::default()
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn test_text_content() {
+            let output = text_content(&parse("this is *hoge* and `fuga`"));
+            assert_eq!(output, "this is hoge and fuga".to_string());
+        }
+
+        #[test]
+        fn test_escape() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: String,
+                expected: Vec<InlineNode>,
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should keep escaped asterisks as text"),
+                    input: String::from(r"2\*3\*4 \a\\"),
+                    expected: vec![inline_text!(r"2*3*4 \a\")],
+                },
+                TestCase {
+                    it: String::from("should not parse escaped brackets as link"),
+                    input: String::from(r"\[a](b) and \`c`"),
+                    expected: vec![inline_text!("[a](b) and `c`")],
+                },
+                TestCase {
+                    it: String::from("should not escape in code span"),
+                    input: String::from(r"`a\*b` ``c`d``"),
+                    expected: vec![
+                        InlineNode::Code {
+                            value: r"a\*b".to_string(),
+                            span: Default::default(),
+                        },
+                        inline_text!(" "),
+                        InlineNode::Code {
+                            value: "c`d".to_string(),
+                            span: Default::default(),
+                        },
+                    ],
+                },
+                TestCase {
+                    it: String::from("should unescape link destination"),
+                    input: String::from(r"[*a\**](b\)c)"),
+                    expected: vec![InlineNode::Link {
+                        href: "b)c".to_string(),
+                        title: None,
+                        children: vec![InlineNode::Emphasis {
+                            children: vec![inline_text!("a*")],
+                            span: Default::default(),
+                        }],
+                        span: Default::default(),
+                    }],
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let output = parse(&test_case.input);
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+            assert_eq!(unescape(r"\| \a \\"), r"| \a \");
+        }
+
+        #[test]
+        fn test_delimiter_run() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: String,
+                expected: Vec<InlineNode>,
+            }
+            let em = |children: Vec<InlineNode>| InlineNode::Emphasis {
+                children,
+                span: Default::default(),
+            };
+            let strong = |children: Vec<InlineNode>| InlineNode::Strong {
+                children,
+                span: Default::default(),
+            };
+            let test_cases = [
+                TestCase {
+                    it: String::from("should nest overlapping runs"),
+                    input: String::from("*a **b* c**"),
+                    expected: vec![em(vec![
+                        inline_text!("a "),
+                        em(vec![em(vec![inline_text!("b")]), inline_text!(" c")]),
+                    ])],
+                },
+                TestCase {
+                    it: String::from("should not parse emphasis in code span"),
+                    input: String::from("*a `*x*` b*"),
+                    expected: vec![em(vec![
+                        inline_text!("a "),
+                        InlineNode::Code {
+                            value: "*x*".to_string(),
+                            span: Default::default(),
+                        },
+                        inline_text!(" b"),
+                    ])],
+                },
+                TestCase {
+                    it: String::from("should parse underscore"),
+                    input: String::from("_a_ __b__ snake_case_name"),
+                    expected: vec![
+                        em(vec![inline_text!("a")]),
+                        inline_text!(" "),
+                        strong(vec![inline_text!("b")]),
+                        inline_text!(" snake_case_name"),
+                    ],
+                },
+                TestCase {
+                    it: String::from("should not open emphasis before whitespace"),
+                    input: String::from("a * b * c*d*"),
+                    expected: vec![inline_text!("a * b * c"), em(vec![inline_text!("d")])],
+                },
+                TestCase {
+                    it: String::from("should follow rule of 3"),
+                    input: String::from("*a**b*"),
+                    expected: vec![em(vec![inline_text!("a**b")])],
+                },
+                TestCase {
+                    it: String::from("should not match markers across link text"),
+                    input: String::from("*a_ [b*](c)*"),
+                    expected: vec![em(vec![
+                        inline_text!("a_ "),
+                        InlineNode::Link {
+                            href: "c".to_string(),
+                            title: None,
+                            children: vec![inline_text!("b*")],
+                            span: Default::default(),
+                        },
+                    ])],
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let output = parse(&test_case.input);
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+
+        #[test]
+        fn test_pathological_delimiters() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: String,
+                expected: Vec<InlineNode>,
+            }
+            let em = || InlineNode::Emphasis {
+                children: vec![inline_text!("a")],
+                span: Default::default(),
+            };
+            let mut ems = vec![em()];
+            for _ in 1..20000 {
+                ems.extend([inline_text!(" "), em()]);
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should leave many closers without opener as text"),
+                    input: "a* ".repeat(20000),
+                    expected: vec![inline_text!("a* ".repeat(20000).trim_end())],
+                },
+                TestCase {
+                    it: String::from("should match many emphasis one by one"),
+                    input: "*a* ".repeat(20000),
+                    expected: ems,
+                },
+                TestCase {
+                    it: String::from("should leave many brackets without destination as text"),
+                    input: "[a](".repeat(20000),
+                    expected: vec![inline_text!("[a](".repeat(20000))],
+                },
+                TestCase {
+                    it: String::from("should leave many links without closed title as text"),
+                    input: "[a](b (".repeat(20000),
+                    expected: vec![inline_text!("[a](b (".repeat(20000))],
+                },
+                TestCase {
+                    it: String::from("should not match closers with openers of another marker"),
+                    input: format!("{}{}", "_a ".repeat(10000), "b* ".repeat(10000)),
+                    expected: vec![inline_text!(format!(
+                        "{}{}",
+                        "_a ".repeat(10000),
+                        "b* ".repeat(10000).trim_end()
+                    ))],
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let output = parse(&test_case.input);
+                assert!(output == test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+
+        #[test]
+        fn test_raw_html() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: String,
+                expected: Vec<InlineNode>,
+            }
+            let html = |value: &str| InlineNode::Html {
+                value: value.to_string(),
+                span: Default::default(),
+            };
+            let test_cases = [
+                TestCase {
+                    it: String::from("should not parse markup in attributes"),
+                    input: String::from(r#"<span title="a *b* c" data-x='_y_'>x</span>"#),
+                    expected: vec![
+                        html(r#"<span title="a *b* c" data-x='_y_'>"#),
+                        inline_text!("x"),
+                        html("</span>"),
+                    ],
+                },
+                TestCase {
+                    it: String::from("should parse comment and declaration"),
+                    input: String::from("a<!-- *b* -->c<!DOCTYPE html>"),
+                    expected: vec![
+                        inline_text!("a"),
+                        html("<!-- *b* -->"),
+                        inline_text!("c"),
+                        html("<!DOCTYPE html>"),
+                    ],
+                },
+                TestCase {
+                    it: String::from("should keep broken tags as text"),
+                    input: String::from(r#"1 < 2 <a href="x <!-- y"#),
+                    expected: vec![inline_text!(r#"1 < 2 <a href="x <!-- y"#)],
+                },
+                TestCase {
+                    it: String::from("should prefer code span starting first"),
+                    input: String::from("`<b>` <i>`</i>`"),
+                    expected: vec![
+                        InlineNode::Code {
+                            value: "<b>".to_string(),
+                            span: Default::default(),
+                        },
+                        inline_text!(" "),
+                        html("<i>"),
+                        InlineNode::Code {
+                            value: "</i>".to_string(),
+                            span: Default::default(),
+                        },
+                    ],
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let output = parse(&test_case.input);
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+
+        #[test]
+        fn test_inline_span() {
+            let line = Line::new("a *b* `c`".to_string(), Position::new(2, 3, 10));
+            let output = inline_parse_lines(&[line]);
+            assert_eq!(
+                output,
+                vec![
+                    InlineNode::Text {
+                        value: "a ".to_string(),
+                        span: Span::new(Position::new(2, 3, 10), Position::new(2, 5, 12)),
+                    },
+                    InlineNode::Emphasis {
+                        children: vec![InlineNode::Text {
+                            value: "b".to_string(),
+                            span: Span::new(Position::new(2, 6, 13), Position::new(2, 7, 14)),
+                        }],
+                        span: Span::new(Position::new(2, 5, 12), Position::new(2, 8, 15)),
+                    },
+                    InlineNode::Text {
+                        value: " ".to_string(),
+                        span: Span::new(Position::new(2, 8, 15), Position::new(2, 9, 16)),
+                    },
+                    InlineNode::Code {
+                        value: "c".to_string(),
+                        span: Span::new(Position::new(2, 9, 16), Position::new(2, 12, 19)),
+                    },
+                ]
+            );
         }
     }
 }
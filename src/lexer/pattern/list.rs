@@ -1,14 +1,23 @@
 pub mod list {
-    use crate::lexer::lexer::lexer::{Content, ElementNode, Token};
-    use crate::lexer::pattern::inline::inline::inline_parse;
+    use crate::lexer::error::error::{Diagnostics, ParseError};
+    use crate::lexer::lexer::lexer::{
+        is_paragraph_continuation, parse, Content, ElementNode, Token,
+    };
+    use crate::lexer::pattern::thematic_break::thematic_break::is_thematic_break;
+    use crate::lexer::source::source::{lines_span, Line};
     use once_cell::sync::Lazy;
     use regex::{Captures, Regex};
+    use std::ops::Range;
 
     static SIMPLE_LIST_PATTERN: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"^([\s\s]*)[\*-]\s(.+)").unwrap());
+        Lazy::new(|| Regex::new(r"^(?P<indent>\s*)(?P<marker>[*+-])(?:[ \t]+|$)").unwrap());
 
-    static NUMBER_LIST_PATTERN: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"^([\s\s]*)\d+\.\s(.+)").unwrap());
+    static TASK_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^\[([ xX])\](?:[ \t]+|$)").unwrap());
+
+    static NUMBER_LIST_PATTERN: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(?P<indent>\s*)(?P<number>\d{1,9})(?P<marker>[.)])(?:[ \t]+|$)").unwrap()
+    });
 
     #[derive(Clone, Copy, Debug)]
     pub enum ListPattern {
@@ -17,115 +26,253 @@ pub mod list {
     }
 
     impl ListPattern {
-        // pub fn value(&self) -> Regex {
-        //     match *self {
-        //         ListPattern::SimpleList => *SIMPLE_LIST_PATTERN,
-        //         ListPattern::NumberList => *NUMBER_LIST_PATTERN,
-        //     }
-        // }
-
-        pub fn parse<'a>(&self, text: &'a String) -> Option<Captures<'a>> {
+        pub fn parse<'a>(&self, text: &'a str) -> Option<Captures<'a>> {
             match self {
                 ListPattern::SimpleList => &SIMPLE_LIST_PATTERN,
                 ListPattern::NumberList => &NUMBER_LIST_PATTERN,
             }
             .captures(text)
         }
+
+        /// Returns indent length of the list item, or `None` when `text` is not an item.
+        pub fn indent(&self, text: &str) -> Option<usize> {
+            self.parse(text)
+                .map(|caps| caps.name("indent").map_or(0, |m| m.as_str().len()))
+        }
+
+        /// Bullet of `*`, `+` or `-`, or delimiter of `.` or `)` after the number.
+        pub fn marker(&self, text: &str) -> Option<char> {
+            self.parse(text)
+                .and_then(|caps| caps.name("marker").and_then(|m| m.as_str().chars().next()))
+        }
+
+        /// Number of the ordered list item, or `None` for the others.
+        fn number(&self, text: &str) -> Option<u32> {
+            self.parse(text)
+                .and_then(|caps| caps.name("number").and_then(|m| m.as_str().parse().ok()))
+        }
+
+        fn is_item(&self, text: &str) -> bool {
+            match self {
+                ListPattern::SimpleList => is_simple_list(text),
+                ListPattern::NumberList => is_number_list(text),
+            }
+        }
     }
 
-    pub fn is_number_list(input: &String) -> bool {
+    pub fn is_number_list(input: &str) -> bool {
         NUMBER_LIST_PATTERN.is_match(input)
     }
 
-    pub fn is_simple_list(input: &String) -> bool {
-        SIMPLE_LIST_PATTERN.is_match(input)
+    /// Whether the item `input` can interrupt a paragraph, which it can only when it is
+    /// not empty, and an ordered list only when it starts at 1, so `2019. It grew` is text.
+    pub fn list_interrupts_paragraph(input: &str) -> bool {
+        let has_content = |caps: &Captures| !input[caps[0].len()..].trim().is_empty();
+        (is_simple_list(input)
+            && SIMPLE_LIST_PATTERN
+                .captures(input)
+                .is_some_and(|caps| has_content(&caps)))
+            || NUMBER_LIST_PATTERN
+                .captures(input)
+                .is_some_and(|caps| caps["number"].parse::<u32>() == Ok(1) && has_content(&caps))
     }
 
-    pub fn parse_list(input: Vec<String>, pattern: ListPattern, now_indent: usize) -> ElementNode {
-        let mut results_node = ElementNode::new(
-            match pattern {
-                ListPattern::SimpleList => Token::Ul,
-                ListPattern::NumberList => Token::Ol,
-            },
-            Content::ElementNodes { value: vec![] },
-            Box::new(ElementNode::Nil),
-        );
-        let mut at = 0;
-        let input_len = input.len();
-        while at < input_len {
-            let text = input.get(at).unwrap();
-            let caps = pattern.parse(text).unwrap();
-            let indent_length = caps.get(1).map_or(0, |m| m.as_str().len());
-            let content = caps.get(2).map_or("", |m| m.as_str());
+    /// Whether `input` is an item of `*`, `+` or `-`, except thematic break such as `* * *`.
+    pub fn is_simple_list(input: &str) -> bool {
+        SIMPLE_LIST_PATTERN.is_match(input) && !is_thematic_break(input)
+    }
 
-            if indent_length > now_indent {
-                let mut start_index = at;
-                loop {
-                    at += 1;
-                    if at == input_len {
-                        break;
-                    }
-                    let _indent_length = pattern
-                        .parse(input.get(at).unwrap())
-                        .unwrap()
-                        .get(1)
-                        .map_or(0, |m| m.as_str().len());
-                    if _indent_length < indent_length {
-                        break;
-                    }
-                }
+    /// Whether the item is checked and the rest of it, when `content` starts with `[ ]` or `[x]`.
+    fn parse_task(content: &Line) -> (Option<bool>, Line) {
+        match TASK_PATTERN.captures(&content.text) {
+            Some(caps) => (Some(&caps[1] != " "), content.skip(caps[0].len())),
+            None => (None, content.clone()),
+        }
+    }
 
-                let parse_result =
-                    parse_list(input[start_index..at].to_owned(), pattern, indent_length);
-                match &mut results_node {
-                    ElementNode::Exist {
-                        ref mut content, ..
-                    } => match &mut **content {
-                        Content::ElementNodes { ref mut value } => {
-                            match value.last_mut() {
-                                Some(value) => match value {
-                                    ElementNode::Exist {
-                                        ref mut children, ..
-                                    } => *children = Box::new(parse_result),
-                                    _ => panic!(""),
-                                },
-                                _ => panic!(""),
-                            };
-                        }
-                        _ => panic!("Type is not correct"),
-                    },
-                    _ => panic!("Type is not correct"),
-                }
-                at -= 1;
+    /// Count of leading spaces and tabs, which is the indent of a continuation line.
+    fn indent_of(line: &Line) -> usize {
+        line.text
+            .bytes()
+            .take_while(|b| *b == b' ' || *b == b'\t')
+            .count()
+    }
+
+    /// Ranges of the items at the head of `input`, whose first line is an item.
+    ///
+    /// A line belongs to the item above when it is indented more than the marker of the item,
+    /// or when it continues the paragraph there. After a blank line, it has to be indented
+    /// up to the content of the item, and an empty item ends there.
+    /// Blank lines at the end of the items are not included.
+    fn item_ranges(input: &[Line], pattern: ListPattern) -> Vec<Range<usize>> {
+        let marker = pattern.marker(&input[0].text);
+        let mut ranges = vec![];
+        let mut start = 0;
+        let mut end = 1;
+        let mut item_indent = pattern.indent(&input[0].text).unwrap_or(0);
+        let mut content_indent = content_start(&input[0], pattern);
+        for (at, line) in input.iter().enumerate().skip(1) {
+            if line.text.trim().is_empty() {
+                continue;
+            }
+            let indent = indent_of(line);
+            let blank_before = end < at;
+            let is_item = is_simple_list(&line.text) || is_number_list(&line.text);
+            let empty_item = end == start + 1
+                && input[start]
+                    .text
+                    .get(content_indent..)
+                    .is_none_or(|rest| rest.trim().is_empty());
+            let belongs = if blank_before && empty_item {
+                false
+            } else if is_item {
+                indent > item_indent
+            } else if blank_before {
+                indent >= content_indent
             } else {
-                match &mut results_node {
-                    ElementNode::Exist {
-                        content: ref mut node_content,
-                        ..
-                    } => match &mut **node_content {
-                        Content::ElementNodes { ref mut value } => value.push(ElementNode::new(
-                            Token::Li,
-                            Content::PlainText {
-                                value: inline_parse(&content.to_string()),
-                            },
-                            Box::new(ElementNode::Nil),
-                        )),
-                        _ => panic!("Type is not correct"),
-                    },
-                    _ => panic!("Type is not correct"),
+                indent > item_indent || is_paragraph_continuation(&line.text)
+            };
+            if belongs {
+                end = at + 1;
+            } else if pattern.is_item(&line.text) && pattern.marker(&line.text) == marker {
+                ranges.push(start..end);
+                start = at;
+                end = at + 1;
+                item_indent = pattern.indent(&line.text).unwrap_or(0);
+                content_indent = content_start(line, pattern);
+            } else {
+                break;
+            }
+        }
+        ranges.push(start..end);
+        ranges
+    }
+
+    /// Byte offset of the content after the marker of the item `line`, which is also
+    /// the indent of its continuation lines.
+    ///
+    /// The content follows the marker by 1 to 4 spaces. With more spaces, or when the item
+    /// is empty, the content is indented by 1 space from the marker, following CommonMark.
+    fn content_start(line: &Line, pattern: ListPattern) -> usize {
+        let caps = match pattern.parse(&line.text) {
+            Some(caps) => caps,
+            None => return line.text.len(),
+        };
+        let marker_end = caps.name("marker").map_or(0, |m| m.end());
+        let spaces = caps[0].len() - marker_end;
+        if (1..=4).contains(&spaces) && caps[0].len() < line.text.len() {
+            caps[0].len()
+        } else {
+            marker_end + 1
+        }
+    }
+
+    /// Count of the lines at the head of `input` which belong to the list of its first item.
+    ///
+    /// An item at the same level with another bullet or delimiter starts a new list.
+    pub fn list_block_len(input: &[Line], pattern: ListPattern) -> usize {
+        item_ranges(input, pattern)
+            .last()
+            .map_or(1, |range| range.end)
+    }
+
+    /// Whether a blank line separates any of `nodes`.
+    fn has_blank_between(nodes: &[ElementNode]) -> bool {
+        nodes
+            .windows(2)
+            .any(|pair| match (pair[0].span(), pair[1].span()) {
+                (Some(above), Some(below)) => below.start.line > above.end.line + 1,
+                _ => false,
+            })
+    }
+
+    /// Parse the item whose first line has the marker, with its content as blocks.
+    fn parse_item(
+        input: &[Line],
+        pattern: ListPattern,
+        depth: usize,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<ElementNode, ParseError> {
+        let content_indent = content_start(&input[0], pattern);
+        let (checked, head) = parse_task(&input[0].skip(content_indent.min(input[0].text.len())));
+        let lines: Vec<Line> = std::iter::once(head)
+            .chain(
+                input[1..]
+                    .iter()
+                    .map(|line| line.skip(indent_of(line).min(content_indent))),
+            )
+            .collect();
+        Ok(ElementNode::new(
+            Token::Li,
+            Content::ListItem {
+                value: parse(&lines, depth + 1, diagnostics)?,
+                checked,
+            },
+            Box::new(ElementNode::Nil),
+        )
+        .with_span(lines_span(input)))
+    }
+
+    /// Parse list items in `input`, each of which holds blocks parsed recursively.
+    ///
+    /// `now_indent` is the indent of the items belonging to this list level, and `depth`
+    /// is the count of the lists and quotes enclosing it.
+    /// When `diagnostics` is lenient, items with inconsistent indent are
+    /// kept in this level.
+    pub fn parse_list(
+        input: &[Line],
+        pattern: ListPattern,
+        now_indent: usize,
+        depth: usize,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<ElementNode, ParseError> {
+        let ranges = item_ranges(input, pattern);
+        let mut items: Vec<ElementNode> = vec![];
+        let mut loose = false;
+        for (n, range) in ranges.iter().enumerate() {
+            let line = &input[range.start];
+            let indent = pattern.indent(&line.text).unwrap_or(0);
+            if indent != now_indent {
+                let position = line.position(indent);
+                diagnostics.report(ParseError::InconsistentListIndent {
+                    line: position.line,
+                    column: position.column,
+                })?;
+            }
+            let item = parse_item(&input[range.clone()], pattern, depth, diagnostics)?;
+            if let ElementNode::Exist { content, .. } = &item {
+                if let Content::ListItem { value, .. } = &**content {
+                    loose |= has_blank_between(value);
                 }
             }
-            at += 1;
+            loose |= n > 0 && ranges[n - 1].end < range.start;
+            items.push(item);
         }
-        results_node
+        let end = ranges.last().map_or(input.len(), |range| range.end);
+        Ok(ElementNode::new(
+            match pattern {
+                ListPattern::SimpleList => Token::Ul,
+                ListPattern::NumberList => Token::Ol,
+            },
+            Content::List {
+                value: items,
+                start: pattern.number(&input[0].text),
+                loose,
+            },
+            Box::new(ElementNode::Nil),
+        )
+        .with_span(lines_span(&input[..end])))
     }
 
     #[cfg(test)]
     mod test_list {
         use super::*;
-        use crate::lexer::lexer::lexer::Content::{ElementNodes, PlainText};
-        use crate::lexer::pattern::list::list::ListPattern::NumberList;
-        use crate::{content_element_nodes, content_plain_text, element_node};
+        use crate::lexer::lexer::lexer::InlineNode;
+        use crate::lexer::source::source::{source_lines, Position, Span};
+        use crate::{
+            content_inline_nodes, content_list, content_list_item, element_node, inline_text,
+            vec_string,
+        };
 
         #[test]
         fn test_is_number_list() {
@@ -225,40 +372,70 @@ pub mod list {
                     pattern: ListPattern::SimpleList,
                     expected: element_node! {
                         tag: Token::Ul,
-                        content: content_element_nodes![
+                        content: content_list![
                             element_node! {
                                 tag: Token::Li,
-                                content: content_plain_text!("hogehoge".to_string()),
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("hogehoge")],
+                                    },
+                                ],
                             },
                             element_node! {
                                 tag: Token::Li,
-                                content: content_plain_text!("hogehoge1".to_string()),
-                                children: element_node! {
-                                    tag: Token::Ul,
-                                    content: content_element_nodes![
-                                        element_node! {
-                                            tag: Token::Li,
-                                            content: content_plain_text!("this is test".to_string()),
-                                        },
-                                        element_node! {
-                                            tag: Token::Li,
-                                            content: content_plain_text!("hogehoge3".to_string()),
-                                            children: element_node! {
-                                                tag: Token::Ul,
-                                                content: content_element_nodes![
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("hogehoge1")],
+                                    },
+                                    element_node! {
+                                        tag: Token::Ul,
+                                        content: content_list![
+                                            element_node! {
+                                                tag: Token::Li,
+                                                content: content_list_item![
                                                     element_node! {
-                                                        tag: Token::Li,
-                                                        content: content_plain_text!("hoge 4".to_string()),
-                                                    }
+                                                        tag: Token::P,
+                                                        content: content_inline_nodes![inline_text!("this is test")],
+                                                    },
+                                                ],
+                                            },
+                                            element_node! {
+                                                tag: Token::Li,
+                                                content: content_list_item![
+                                                    element_node! {
+                                                        tag: Token::P,
+                                                        content: content_inline_nodes![inline_text!("hogehoge3")],
+                                                    },
+                                                    element_node! {
+                                                        tag: Token::Ul,
+                                                        content: content_list![
+                                                            element_node! {
+                                                                tag: Token::Li,
+                                                                content: content_list_item![
+                                                                    element_node! {
+                                                                        tag: Token::P,
+                                                                        content: content_inline_nodes![inline_text!("hoge 4")],
+                                                                    },
+                                                                ],
+                                                            }
+                                                        ]
+                                                    },
                                                 ]
-                                            }
-                                        },
-                                    ],
-                                }
+                                            },
+                                        ],
+                                    },
+                                ]
                             },
                             element_node! {
                                 tag: Token::Li,
-                                content: content_plain_text!("hogehoge4".to_string()),
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("hogehoge4")],
+                                    },
+                                ],
                             },
                         ]
                     },
@@ -272,50 +449,401 @@ pub mod list {
                     pattern: ListPattern::NumberList,
                     expected: element_node! {
                         tag: Token::Ol,
-                        content: content_element_nodes![
+                        content: content_list![
+                            start: 1,
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("hoge1")],
+                                    },
+                                ],
+                            },
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("hoge2")],
+                                    },
+                                    element_node! {
+                                        tag: Token::Ol,
+                                        content: content_list![
+                                            start: 1,
+                                            element_node! {
+                                                tag: Token::Li,
+                                                content: content_list_item![
+                                                    element_node! {
+                                                        tag: Token::P,
+                                                        content: content_inline_nodes![inline_text!("aaa")],
+                                                    },
+                                                ],
+                                            },
+                                            element_node! {
+                                                tag: Token::Li,
+                                                content: content_list_item![
+                                                    element_node! {
+                                                        tag: Token::P,
+                                                        content: content_inline_nodes![inline_text!("ccc")],
+                                                    },
+                                                    element_node! {
+                                                        tag: Token::Ol,
+                                                        content: content_list![
+                                                            start: 1,
+                                                            element_node! {
+                                                                tag: Token::Li,
+                                                                content: content_list_item![
+                                                                    element_node! {
+                                                                        tag: Token::P,
+                                                                        content: content_inline_nodes![inline_text!("ddd")],
+                                                                    },
+                                                                ],
+                                                            }
+                                                        ]
+                                                    },
+                                                ]
+                                            },
+                                        ],
+                                    },
+                                ]
+                            },
+                        ]
+                    },
+                },
+                TestCase {
+                    it: String::from("should correctly parse task list"),
+                    input: vec_string!["- [ ] todo", "- [x] done", "- [X]", "- [y] no", "- [x]no"],
+                    pattern: ListPattern::SimpleList,
+                    expected: element_node! {
+                        tag: Token::Ul,
+                        content: content_list![
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    checked: false,
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("todo")],
+                                    },
+                                ],
+                            },
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    checked: true,
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("done")],
+                                    },
+                                ],
+                            },
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![checked: true],
+                            },
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("[y] no")],
+                                    },
+                                ],
+                            },
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("[x]no")],
+                                    },
+                                ],
+                            },
+                        ]
+                    },
+                },
+                TestCase {
+                    it: String::from("should take up to four spaces after the marker as indent"),
+                    input: vec_string!["-  a", "-    b", "-     c"],
+                    pattern: ListPattern::SimpleList,
+                    expected: element_node! {
+                        tag: Token::Ul,
+                        content: content_list![
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("a")],
+                                    },
+                                ],
+                            },
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("b")],
+                                    },
+                                ],
+                            },
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("    c")],
+                                    },
+                                ],
+                            },
+                        ]
+                    },
+                },
+                TestCase {
+                    it: String::from("should correctly parse empty items"),
+                    input: vec_string!["1.", "2. a", "3.   "],
+                    pattern: ListPattern::NumberList,
+                    expected: element_node! {
+                        tag: Token::Ol,
+                        content: content_list![
+                            start: 1,
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![],
+                            },
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("a")],
+                                    },
+                                ],
+                            },
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![],
+                            },
+                        ]
+                    },
+                },
+                TestCase {
+                    it: String::from("should end empty item at blank line"),
+                    input: vec_string!["-", "  a", "-", "", "  b"],
+                    pattern: ListPattern::SimpleList,
+                    expected: element_node! {
+                        tag: Token::Ul,
+                        content: content_list![
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("a")],
+                                    },
+                                ],
+                            },
+                            element_node! {
+                                tag: Token::Li,
+                                content: content_list_item![],
+                            },
+                        ]
+                    },
+                },
+            ];
+
+            for test_case in test_cases.iter() {
+                let output = parse_list(
+                    &source_lines(test_case.input.clone()),
+                    test_case.pattern,
+                    0,
+                    0,
+                    &mut Diagnostics::strict(),
+                )
+                .map(ElementNode::without_span);
+                assert_eq!(
+                    output,
+                    Ok(test_case.expected.clone()),
+                    "Failed: {}\n",
+                    test_case.it
+                );
+            }
+        }
+
+        #[test]
+        fn test_parse_inconsistent_list() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: Vec<String>,
+                expected: ParseError,
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should return error when nested list has no parent item"),
+                    input: vec_string!["  * hoge", "* fuga"],
+                    expected: ParseError::InconsistentListIndent { line: 1, column: 3 },
+                },
+                TestCase {
+                    it: String::from("should return error when dedent does not match any level"),
+                    input: vec_string!["* hoge", "    * fuga", "  * piyo"],
+                    expected: ParseError::InconsistentListIndent { line: 3, column: 3 },
+                },
+            ];
+
+            for test_case in test_cases.iter() {
+                let output = parse_list(
+                    &source_lines(test_case.input.clone()),
+                    ListPattern::SimpleList,
+                    0,
+                    0,
+                    &mut Diagnostics::strict(),
+                );
+                assert_eq!(
+                    output,
+                    Err(test_case.expected.clone()),
+                    "Failed: {}\n",
+                    test_case.it
+                );
+            }
+        }
+
+        #[test]
+        fn test_normalize_inconsistent_list() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: Vec<String>,
+                expected: ElementNode,
+                diagnostics: Vec<ParseError>,
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should lift nested list without parent item"),
+                    input: vec_string!["  * hoge", "* fuga"],
+                    expected: element_node! {
+                        tag: Token::Ul,
+                        content: content_list![
                             element_node! {
                                 tag: Token::Li,
-                                content: content_plain_text!("hoge1".to_string()),
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("hoge")],
+                                    },
+                                ],
                             },
                             element_node! {
                                 tag: Token::Li,
-                                content: content_plain_text!("hoge2".to_string()),
-                                children: element_node! {
-                                    tag: Token::Ol,
-                                    content: content_element_nodes![
+                                content: content_list_item![
+                                    element_node! {
+                                        tag: Token::P,
+                                        content: content_inline_nodes![inline_text!("fuga")],
+                                    },
+                                ],
+                            },
+                        ],
+                    },
+                    diagnostics: vec![ParseError::InconsistentListIndent { line: 1, column: 3 }],
+                },
+                TestCase {
+                    it: String::from("should merge unmatched dedent into open level"),
+                    input: vec_string!["* hoge", "    * fuga", "  * piyo"],
+                    expected: element_node! {
+                        tag: Token::Ul,
+                        content: content_list![element_node! {
+                            tag: Token::Li,
+                            content: content_list_item![
+                                element_node! {
+                                    tag: Token::P,
+                                    content: content_inline_nodes![inline_text!("hoge")],
+                                },
+                                element_node! {
+                                    tag: Token::Ul,
+                                    content: content_list![
                                         element_node! {
                                             tag: Token::Li,
-                                            content: content_plain_text!("aaa".to_string()),
+                                            content: content_list_item![
+                                                element_node! {
+                                                    tag: Token::P,
+                                                    content: content_inline_nodes![inline_text!("fuga")],
+                                                },
+                                            ],
                                         },
                                         element_node! {
                                             tag: Token::Li,
-                                            content: content_plain_text!("ccc".to_string()),
-                                            children: element_node! {
-                                                tag: Token::Ol,
-                                                content: content_element_nodes![
-                                                    element_node! {
-                                                        tag: Token::Li,
-                                                        content: content_plain_text!("ddd".to_string()),
-                                                    }
-                                                ]
-                                            }
+                                            content: content_list_item![
+                                                element_node! {
+                                                    tag: Token::P,
+                                                    content: content_inline_nodes![inline_text!("piyo")],
+                                                },
+                                            ],
                                         },
                                     ],
-                                }
-                            },
-                        ]
+                                },
+                            ],
+                        }],
                     },
+                    diagnostics: vec![ParseError::InconsistentListIndent { line: 3, column: 3 }],
                 },
             ];
 
             for test_case in test_cases.iter() {
+                let mut diagnostics = Diagnostics::lenient();
                 let output = parse_list(
-                    test_case.input.iter().map(|s| s.into()).collect(),
-                    test_case.pattern,
+                    &source_lines(test_case.input.clone()),
+                    ListPattern::SimpleList,
+                    0,
                     0,
+                    &mut diagnostics,
+                )
+                .map(ElementNode::without_span);
+                assert_eq!(
+                    output,
+                    Ok(test_case.expected.clone()),
+                    "Failed: {}\n",
+                    test_case.it
+                );
+                assert_eq!(
+                    diagnostics.into_errors(),
+                    test_case.diagnostics,
+                    "Failed: {}\n",
+                    test_case.it
                 );
-                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
             }
         }
+
+        #[test]
+        fn test_parse_list_span() {
+            let input = source_lines(vec_string!["* hoge", "  * fuga", "* piyo"]);
+            let output = parse_list(
+                &input,
+                ListPattern::SimpleList,
+                0,
+                0,
+                &mut Diagnostics::strict(),
+            )
+            .unwrap();
+            let items = match &output {
+                ElementNode::Exist { content, .. } => match &**content {
+                    Content::List { value, .. } => value.clone(),
+                    _ => unreachable!(),
+                },
+                ElementNode::Nil => unreachable!(),
+            };
+            assert_eq!(
+                output.span(),
+                Some(&Span::new(Position::new(1, 1, 0), Position::new(3, 7, 22)))
+            );
+            assert_eq!(
+                items[0].span(),
+                Some(&Span::new(Position::new(1, 1, 0), Position::new(2, 9, 15)))
+            );
+            assert_eq!(
+                items[1].span(),
+                Some(&Span::new(Position::new(3, 1, 16), Position::new(3, 7, 22)))
+            );
+        }
     }
 }
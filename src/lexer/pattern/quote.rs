@@ -1,33 +1,34 @@
 pub mod quote {
+    use crate::lexer::source::source::Line;
     use once_cell::sync::Lazy;
     use regex::Regex;
 
     static CODE_BLOCK_PAREN_PATTERN: Lazy<Regex> =
         Lazy::new(|| Regex::new(r"^>\s*([\s>]*.+)").unwrap());
 
-    pub fn is_quote_block(input: &String) -> bool {
-        match input.chars().nth(0) {
-            Some(c) => c == '>',
-            None => false,
-        }
+    pub fn is_quote_block(input: &str) -> bool {
+        input.starts_with('>')
     }
 
-    pub fn enclose_quote(input: Vec<String>) -> Vec<String> {
+    pub fn enclose_quote(input: &[Line]) -> Vec<Line> {
         input
             .iter()
-            .map(|s| {
-                let rslt = CODE_BLOCK_PAREN_PATTERN.captures(s);
-                match rslt {
-                    Some(c) => c.get(1).map_or(s.to_string(), |m| m.as_str().to_string()),
-                    None => s.to_string(),
+            .map(|line| {
+                let rslt = CODE_BLOCK_PAREN_PATTERN.captures(&line.text);
+                match rslt.and_then(|c| c.get(1)) {
+                    Some(m) => line.skip(m.start()),
+                    // blank line in the quote, such as `>`
+                    None if line.text.starts_with('>') => line.skip(1),
+                    None => line.clone(),
                 }
             })
-            .collect::<Vec<String>>()
+            .collect::<Vec<Line>>()
     }
 
     #[cfg(test)]
     mod test_quote {
         use super::*;
+        use crate::lexer::source::source::{source_lines, Position};
 
         #[test]
         fn test_is_quote_block() {
@@ -59,15 +60,16 @@ pub mod quote {
 
         #[test]
         fn test_enclose_quote() {
-            let input = vec!["> hogehoge", "> this is test", "aaa"]
+            let input: Vec<String> = ["> hogehoge", "> this is test", "aaa"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect();
-            let expected: Vec<String> = vec!["hogehoge", "this is test", "aaa"]
-                .iter()
-                .map(|s| s.to_string())
-                .collect();
-            let output = enclose_quote(input);
+            let expected = vec![
+                Line::new("hogehoge".to_string(), Position::new(1, 3, 2)),
+                Line::new("this is test".to_string(), Position::new(2, 3, 13)),
+                Line::new("aaa".to_string(), Position::new(3, 1, 26)),
+            ];
+            let output = enclose_quote(&source_lines(input));
             assert_eq!(output, expected);
         }
     }
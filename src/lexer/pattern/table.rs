@@ -1,89 +1,196 @@
 pub mod table {
-    use crate::lexer::lexer::lexer::{Align, Table, TableHead};
+    use crate::lexer::error::error::{Diagnostics, ParseError};
+    use crate::lexer::lexer::lexer::{Align, InlineNode, Table, TableHead, TableRow};
+    use crate::lexer::pattern::inline::inline::{inline_parse_lines, is_escapable};
+    use crate::lexer::source::source::{Line, Position};
     use once_cell::sync::Lazy;
     use regex::Regex;
 
     static TABLE_HEAD_PATTERN: Lazy<Regex> =
         Lazy::new(|| Regex::new(r"(?:\s?(.+?)\s?\|)+?").unwrap());
 
-    static LEFT_COLUMN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^:-+$").unwrap());
+    static LEFT_COLUMN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^:?-+$").unwrap());
 
     static CENTER_COLUMN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^:-+:$").unwrap());
 
     static RIGHT_COLUMN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^-+:$").unwrap());
 
-    pub fn is_table_block_start(input: &String) -> bool {
-        match input.chars().nth(0) {
-            Some(c) => {
-                if c == '|' && input.chars().last().unwrap() == '|' {
-                    TABLE_HEAD_PATTERN.is_match(&input[1..])
-                } else {
-                    false
-                }
-            }
-            None => false,
-        }
+    pub fn is_table_block_start(input: &str) -> bool {
+        input.len() > 1
+            && input.starts_with('|')
+            && input.ends_with('|')
+            && !is_escaped(input, input.len() - 1)
+            && TABLE_HEAD_PATTERN.is_match(&input[1..])
+    }
+
+    /// Whether the character at byte `at` of `input` follows a backslash escaping it.
+    fn is_escaped(input: &str, at: usize) -> bool {
+        let backslashes = input[..at].chars().rev().take_while(|c| *c == '\\').count();
+        backslashes % 2 == 1
     }
 
-    fn get_table_column_name(input: &String) -> Vec<String> {
-        let mut rslt: Vec<String> = vec![];
-        for mat in TABLE_HEAD_PATTERN.captures_iter(input) {
-            rslt.push(mat.get(1).map_or("", |m| m.as_str()).trim().to_string())
+    /// Split row `input` after its leading `|` into cells with the byte offset of their content.
+    ///
+    /// `\|` does not split cells, and text after the last `|` is dropped.
+    fn split_cells(input: &str) -> Vec<(usize, &str)> {
+        let mut rslt: Vec<(usize, &str)> = vec![];
+        let mut start = 0;
+        let mut chars = input.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c == '\\' {
+                chars.next_if(|(_, c)| is_escapable(c));
+            } else if c == '|' {
+                let cell = &input[start..i];
+                // spaces next to the separators are not a part of the cell
+                rslt.push((start + cell.len() - cell.trim_start().len(), cell.trim()));
+                start = i + 1;
+            }
         }
         rslt
     }
 
-    fn get_column_align(input: &String) -> Vec<Align> {
-        let mut rslt: Vec<Align> = vec![];
-        for mat in TABLE_HEAD_PATTERN.captures_iter(input) {
-            let cell = mat.get(1).map_or("", |m| m.as_str()).trim().to_string();
-            if LEFT_COLUMN.is_match(&cell) {
-                rslt.push(Align::Left);
-            } else if RIGHT_COLUMN.is_match(&cell) {
-                rslt.push(Align::Right);
-            } else if CENTER_COLUMN.is_match(&cell) {
-                rslt.push(Align::Center);
+    /// Parse `cell` at byte `start` of row `line`, after its leading `|`, as inline content.
+    fn parse_cell(line: &Line, (start, cell): (usize, &str)) -> Vec<InlineNode> {
+        // `\|` is a pipe even in code spans, so it is unescaped before inline parsing
+        let cell = cell.replace(r"\|", "|");
+        inline_parse_lines(&[Line::new(cell, line.position(start + 1))])
+    }
+
+    /// Returns alignment of each cell of delimiter row `input` with its position.
+    ///
+    /// Cells which are not delimiters have `None` as alignment.
+    fn get_column_align(input: &Line) -> Vec<(Option<Align>, Position)> {
+        let mut rslt: Vec<(Option<Align>, Position)> = vec![];
+        for (start, cell) in split_cells(&input.text[1..]) {
+            let column = input.position(start + 1);
+            if LEFT_COLUMN.is_match(cell) {
+                rslt.push((Some(Align::Left), column));
+            } else if RIGHT_COLUMN.is_match(cell) {
+                rslt.push((Some(Align::Right), column));
+            } else if CENTER_COLUMN.is_match(cell) {
+                rslt.push((Some(Align::Center), column));
+            } else {
+                rslt.push((None, column));
             }
         }
         rslt
     }
 
-    fn get_table_head_info(input: Vec<String>) -> Vec<TableHead> {
-        let head = get_table_column_name(&input.get(0).unwrap()[1..].to_string());
-        let align = get_column_align(&input.get(1).unwrap()[1..].to_string());
+    /// Returns head of the table and whether `input[1]` is its delimiter row.
+    ///
+    /// Missing alignments fall back to [`Align::Left`] when `diagnostics` is lenient.
+    fn get_table_head_info(
+        input: &[Line],
+        diagnostics: &mut Diagnostics,
+    ) -> Result<(Vec<TableHead>, bool), ParseError> {
+        let head = split_cells(&input[0].text[1..])
+            .into_iter()
+            .map(|cell| parse_cell(&input[0], cell));
+        let align = match input.get(1) {
+            Some(delimiter) if is_table_block_start(&delimiter.text) => {
+                let align = get_column_align(delimiter);
+                if align.iter().any(|(align, _)| align.is_some()) {
+                    Some((align, delimiter.end()))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        let (align, end) = match align {
+            Some(align) => align,
+            None => {
+                let next_line = input[0].end().line + 1;
+                diagnostics.report(ParseError::MalformedTable {
+                    line: next_line,
+                    column: 1,
+                })?;
+                (vec![], input[0].end())
+            }
+        };
+        let has_delimiter = !align.is_empty();
         let mut rslt: Vec<TableHead> = vec![];
-        for i in 0..head.len() {
-            rslt.push(TableHead::new(
-                head.get(i).unwrap().to_string(),
-                *align.get(i).unwrap(),
-            ))
+        for (i, cell) in head.enumerate() {
+            let align = match align.get(i) {
+                Some((Some(align), _)) => *align,
+                Some((None, position)) => {
+                    diagnostics.report(ParseError::MalformedTable {
+                        line: position.line,
+                        column: position.column,
+                    })?;
+                    Align::Left
+                }
+                None => {
+                    if has_delimiter {
+                        diagnostics.report(ParseError::MalformedTable {
+                            line: end.line,
+                            column: end.column,
+                        })?;
+                    }
+                    Align::Left
+                }
+            };
+            rslt.push(TableHead::new(cell, align));
         }
-        rslt
+        Ok((rslt, has_delimiter))
     }
 
-    fn parse_table_body(input: Vec<String>) -> (Vec<Vec<String>>, usize) {
+    /// Parse body rows at the head of `input`, each with as many cells as `width`.
+    ///
+    /// When `diagnostics` is lenient, missing cells are filled with empty ones
+    /// and cells beyond `width` are dropped.
+    fn parse_table_body(
+        input: &[Line],
+        width: usize,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<(Vec<TableRow>, usize), ParseError> {
         let mut now_at: usize = 0;
-        let mut rows: Vec<Vec<String>> = vec![];
-        for item in input.into_iter() {
-            if !is_table_block_start(&item) {
+        let mut rows: Vec<TableRow> = vec![];
+        for item in input.iter() {
+            if !is_table_block_start(&item.text) {
                 break;
             }
-            rows.push(get_table_column_name(&item[1..].to_string()));
+            let cells = split_cells(&item.text[1..]);
+            if cells.len() != width {
+                let position = match cells.get(width) {
+                    Some((start, _)) => item.position(start + 1),
+                    None => item.end(),
+                };
+                diagnostics.report(ParseError::MalformedTable {
+                    line: position.line,
+                    column: position.column,
+                })?;
+            }
+            let mut row: TableRow = cells
+                .into_iter()
+                .take(width)
+                .map(|cell| parse_cell(item, cell))
+                .collect();
+            row.resize(width, vec![]);
+            rows.push(row);
             now_at += 1;
         }
-        (rows, now_at)
+        Ok((rows, now_at))
     }
 
-    pub fn parse_table(input: Vec<String>) -> (Table, usize) {
-        let table_head = get_table_head_info(input[0..2].to_vec());
-        let (rows, skip) = parse_table_body(input[2..].to_vec());
-        (Table::new(table_head, rows), skip + 2)
+    /// Parse table starting at `input[0]`.
+    ///
+    /// Returns the table and the count of consumed lines.
+    pub fn parse_table(
+        input: &[Line],
+        diagnostics: &mut Diagnostics,
+    ) -> Result<(Table, usize), ParseError> {
+        let (table_head, has_delimiter) = get_table_head_info(input, diagnostics)?;
+        let head_length = if has_delimiter { 2 } else { 1 };
+        let (rows, skip) = parse_table_body(&input[head_length..], table_head.len(), diagnostics)?;
+        Ok((Table::new(table_head, rows), skip + head_length))
     }
 
     #[cfg(test)]
     mod table_test {
         use super::*;
-        use crate::{table, vec_string};
+        use crate::lexer::source::source::source_lines;
+        use crate::{inline_text, table, vec_string};
 
         #[test]
         fn test_is_table_block_start() {
@@ -110,6 +217,11 @@ pub mod table {
                     input: String::from("|  head1  | head2 | head3"),
                     expected: false,
                 },
+                TestCase {
+                    it: String::from("should return false when last pipe is escaped"),
+                    input: String::from("|  head1  | head2 \\|"),
+                    expected: false,
+                },
             ];
 
             for test_case in test_cases.iter() {
@@ -120,7 +232,7 @@ pub mod table {
 
         #[test]
         fn test_parse_table() {
-            let input = vec_string![
+            let input: Vec<String> = vec_string![
                 "|  head1  | head2 | head3|",
                 "|:----:|-----:|:-----|",
                 "|  aaa1  | bbb1 | ccc1|",
@@ -130,20 +242,201 @@ pub mod table {
             let expected = (
                 table! {
                     head: vec![
-                        TableHead::new("head1".to_string(), Align::Center),
-                        TableHead::new("head2".to_string(), Align::Right),
-                        TableHead::new("head3".to_string(), Align::Left),
+                        TableHead::new(vec![inline_text!("head1")], Align::Center),
+                        TableHead::new(vec![inline_text!("head2")], Align::Right),
+                        TableHead::new(vec![inline_text!("head3")], Align::Left),
                     ],
                     body: vec![
-                        vec_string!["aaa1", "bbb1", "ccc1"],
-                        vec_string!["aaa2", "bbb2", "ccc2"],
+                        vec![
+                            vec![inline_text!("aaa1")],
+                            vec![inline_text!("bbb1")],
+                            vec![inline_text!("ccc1")],
+                        ],
+                        vec![
+                            vec![inline_text!("aaa2")],
+                            vec![inline_text!("bbb2")],
+                            vec![inline_text!("ccc2")],
+                        ],
                     ],
                 },
                 4,
             );
 
-            let output = parse_table(input);
-            assert_eq!(output.0, expected.0);
+            let (table, len) =
+                parse_table(&source_lines(input), &mut Diagnostics::strict()).unwrap();
+            assert_eq!((table.without_span(), len), expected);
+        }
+
+        #[test]
+        fn test_parse_table_with_escaped_pipe() {
+            let input: Vec<String> =
+                vec_string![r"| a \| b | c\\|", "|---|---|", r"| \*d\* | \e |",];
+
+            let expected = (
+                table! {
+                    head: vec![
+                        TableHead::new(vec![inline_text!("a | b")], Align::Left),
+                        TableHead::new(vec![inline_text!(r"c\")], Align::Left),
+                    ],
+                    body: vec![vec![vec![inline_text!("*d*")], vec![inline_text!(r"\e")]]],
+                },
+                3,
+            );
+
+            let (table, len) =
+                parse_table(&source_lines(input), &mut Diagnostics::strict()).unwrap();
+            assert_eq!((table.without_span(), len), expected);
+        }
+
+        #[test]
+        fn test_parse_malformed_table() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: Vec<String>,
+                expected: ParseError,
+            }
+
+            let test_cases = [
+                TestCase {
+                    it: String::from("should return error when delimiter row is missing"),
+                    input: vec_string!["| head1 | head2 |"],
+                    expected: ParseError::MalformedTable { line: 2, column: 1 },
+                },
+                TestCase {
+                    it: String::from("should return error when delimiter row is short"),
+                    input: vec_string!["| head1 | head2 |", "|:---|"],
+                    expected: ParseError::MalformedTable { line: 2, column: 7 },
+                },
+                TestCase {
+                    it: String::from("should return error when delimiter cell is not delimiter"),
+                    input: vec_string!["| head1 | head2 |", "|:---| aaa |"],
+                    expected: ParseError::MalformedTable { line: 2, column: 8 },
+                },
+                TestCase {
+                    it: String::from("should return error when body row has extra cells"),
+                    input: vec_string!["| head1 |", "|---|", "| aaa1 | bbb1 |"],
+                    expected: ParseError::MalformedTable {
+                        line: 3,
+                        column: 10,
+                    },
+                },
+                TestCase {
+                    it: String::from("should return error when body row lacks cells"),
+                    input: vec_string!["| head1 | head2 |", "|---|---|", "| aaa1 |"],
+                    expected: ParseError::MalformedTable { line: 3, column: 9 },
+                },
+            ];
+
+            for test_case in test_cases.iter() {
+                let output = parse_table(
+                    &source_lines(test_case.input.clone()),
+                    &mut Diagnostics::strict(),
+                );
+                assert_eq!(
+                    output,
+                    Err(test_case.expected.clone()),
+                    "Failed: {}\n",
+                    test_case.it
+                );
+            }
+        }
+
+        #[test]
+        fn test_recover_malformed_table() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: Vec<String>,
+                expected: (Table, usize),
+                diagnostics: Vec<ParseError>,
+            }
+
+            let test_cases = [
+                TestCase {
+                    it: String::from(
+                        "should treat second row as body when delimiter row is missing",
+                    ),
+                    input: vec_string!["| head1 | head2 |", "| aaa1 | bbb1 |"],
+                    expected: (
+                        table! {
+                            head: vec![
+                                TableHead::new(vec![inline_text!("head1")], Align::Left),
+                                TableHead::new(vec![inline_text!("head2")], Align::Left),
+                            ],
+                            body: vec![
+                                vec![vec![inline_text!("aaa1")], vec![inline_text!("bbb1")]],
+                            ],
+                        },
+                        2,
+                    ),
+                    diagnostics: vec![ParseError::MalformedTable { line: 2, column: 1 }],
+                },
+                TestCase {
+                    it: String::from("should fall back to left align when delimiter row is short"),
+                    input: vec_string!["| head1 | head2 |", "|---:|", "| aaa1 | bbb1 |"],
+                    expected: (
+                        table! {
+                            head: vec![
+                                TableHead::new(vec![inline_text!("head1")], Align::Right),
+                                TableHead::new(vec![inline_text!("head2")], Align::Left),
+                            ],
+                            body: vec![
+                                vec![vec![inline_text!("aaa1")], vec![inline_text!("bbb1")]],
+                            ],
+                        },
+                        3,
+                    ),
+                    diagnostics: vec![ParseError::MalformedTable { line: 2, column: 7 }],
+                },
+                TestCase {
+                    it: String::from("should normalize body rows to the width of the head"),
+                    input: vec_string![
+                        "| head1 | head2 |",
+                        "|---|---|",
+                        "| aaa1 |",
+                        "| aaa2 | bbb2 | ccc2 |",
+                    ],
+                    expected: (
+                        table! {
+                            head: vec![
+                                TableHead::new(vec![inline_text!("head1")], Align::Left),
+                                TableHead::new(vec![inline_text!("head2")], Align::Left),
+                            ],
+                            body: vec![
+                                vec![vec![inline_text!("aaa1")], vec![]],
+                                vec![vec![inline_text!("aaa2")], vec![inline_text!("bbb2")]],
+                            ],
+                        },
+                        4,
+                    ),
+                    diagnostics: vec![
+                        ParseError::MalformedTable { line: 3, column: 9 },
+                        ParseError::MalformedTable {
+                            line: 4,
+                            column: 17,
+                        },
+                    ],
+                },
+            ];
+
+            for test_case in test_cases.iter() {
+                let mut diagnostics = Diagnostics::lenient();
+                let output = parse_table(&source_lines(test_case.input.clone()), &mut diagnostics)
+                    .map(|(table, len)| (table.without_span(), len));
+                assert_eq!(
+                    output,
+                    Ok(test_case.expected.clone()),
+                    "Failed: {}\n",
+                    test_case.it
+                );
+                assert_eq!(
+                    diagnostics.into_errors(),
+                    test_case.diagnostics,
+                    "Failed: {}\n",
+                    test_case.it
+                );
+            }
         }
     }
 }
@@ -0,0 +1,69 @@
+pub mod thematic_break {
+    use once_cell::sync::Lazy;
+    use regex::Regex;
+
+    static THEMATIC_BREAK_PATTERN: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^ {0,3}(?:(?:\*[ \t]*){3,}|(?:-[ \t]*){3,}|(?:_[ \t]*){3,})$").unwrap()
+    });
+
+    /// Whether `input` is `---`, `***` or `___`, which may have spaces between the markers.
+    pub fn is_thematic_break(input: &str) -> bool {
+        THEMATIC_BREAK_PATTERN.is_match(input)
+    }
+
+    #[cfg(test)]
+    mod test_thematic_break {
+        use super::*;
+
+        #[test]
+        fn test_is_thematic_break() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: String,
+                expected: bool,
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should return true for `---`"),
+                    input: String::from("---"),
+                    expected: true,
+                },
+                TestCase {
+                    it: String::from("should return true for markers with spaces"),
+                    input: String::from("   * * *  "),
+                    expected: true,
+                },
+                TestCase {
+                    it: String::from("should return true for long `_`"),
+                    input: String::from("_____"),
+                    expected: true,
+                },
+                TestCase {
+                    it: String::from("should return false for two markers"),
+                    input: String::from("--"),
+                    expected: false,
+                },
+                TestCase {
+                    it: String::from("should return false for mixed markers"),
+                    input: String::from("-*-"),
+                    expected: false,
+                },
+                TestCase {
+                    it: String::from("should return false for four spaces of indent"),
+                    input: String::from("    ---"),
+                    expected: false,
+                },
+                TestCase {
+                    it: String::from("should return false when text follows"),
+                    input: String::from("--- a"),
+                    expected: false,
+                },
+            ];
+            for test_case in test_cases.iter() {
+                let output = is_thematic_break(&test_case.input);
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+    }
+}
@@ -0,0 +1,1228 @@
+pub mod renderer {
+    use crate::lexer::builder::builder::MdNode;
+    use crate::lexer::code_handler::code_handler::{CodeBlockHandlers, CodeBlockOutput};
+    use crate::lexer::lexer::lexer::{
+        Align, Content, ElementNode, InlineNode, Table, TableHead, TableRow, Token,
+    };
+    use crate::lexer::pattern::inline::inline::text_content;
+    use crate::lexer::sanitizer::sanitizer::HtmlSanitizer;
+    use crate::lexer::source::source::Span;
+    use crate::lexer::url_policy::url_policy::UrlPolicy;
+    use crate::util::string::string::escape_code_string;
+    use once_cell::sync::Lazy;
+    use std::collections::HashMap;
+
+    /// Options for [`MdNode::to_html_string_with`].
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RenderOptions {
+        /// Emit `data-source-line` with the first source line on each block element.
+        pub source_line: bool,
+        /// Also emit `data-source-line-end` with the last source line.
+        pub source_line_end: bool,
+        /// Prefix of every class name, such as `flav-md` in `flav-md-h1`.
+        pub class_prefix: String,
+        /// Classes added to the elements of each tag as they are.
+        pub extra_classes_per_tag: HashMap<HtmlTag, Vec<String>>,
+        /// Emit html in text instead of escaping it, after passing it through `html_sanitizer`.
+        pub allow_raw_html: bool,
+        /// Sanitizer of html emitted with `allow_raw_html`.
+        ///
+        /// `None` emits html as it is, so never set it for markdown from untrusted authors.
+        pub html_sanitizer: Option<HtmlSanitizer>,
+        /// Policy for urls of links and images.
+        pub url_policy: UrlPolicy,
+        /// Handlers replacing fenced code blocks of their languages.
+        pub code_block_handlers: CodeBlockHandlers,
+        /// Emit checkboxes of task list items enabled, with `data-source-line` of their items
+        /// so that the app can toggle them and write back to the source.
+        pub task_checkbox_enabled: bool,
+    }
+
+    impl Default for RenderOptions {
+        fn default() -> Self {
+            RenderOptions {
+                source_line: false,
+                source_line_end: false,
+                class_prefix: "flav-md".to_string(),
+                extra_classes_per_tag: HashMap::new(),
+                allow_raw_html: false,
+                html_sanitizer: Some(HtmlSanitizer::default()),
+                url_policy: UrlPolicy::default(),
+                code_block_handlers: CodeBlockHandlers::default(),
+                task_checkbox_enabled: false,
+            }
+        }
+    }
+
+    /// Element of the output, which is a key of [`RenderOptions::extra_classes_per_tag`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum HtmlTag {
+        /// Element of the block `Token`.
+        Block(Token),
+        Em,
+        Strong,
+        /// `code` of code span, which is `Block(Token::Code)` for code block.
+        InlineCode,
+        A,
+        Img,
+        Br,
+        Thead,
+        Tbody,
+        Tr,
+        Th,
+        Td,
+    }
+
+    impl From<Token> for HtmlTag {
+        fn from(token: Token) -> Self {
+            HtmlTag::Block(token)
+        }
+    }
+
+    static DEFAULT_OPTIONS: Lazy<RenderOptions> = Lazy::new(RenderOptions::default);
+
+    /// Converts the syntax tree to text.
+    ///
+    /// Every method renders flav md html by default, so implement only the
+    /// methods whose output you want to change. `indent` is the count of
+    /// spaces nested blocks are indented with.
+    pub trait Renderer {
+        fn options(&self) -> &RenderOptions {
+            &DEFAULT_OPTIONS
+        }
+
+        /// Render the whole document.
+        fn document(&self, document: &MdNode) -> String {
+            document
+                .element_nodes()
+                .iter()
+                .map(|node| self.element(node, 0))
+                // html blocks removed by the sanitizer leave nothing
+                .filter(|html| !html.is_empty())
+                .collect::<Vec<String>>()
+                .join("\n")
+        }
+
+        /// Render `node` by calling the method for its tag.
+        fn element(&self, node: &ElementNode, indent: usize) -> String {
+            render_element(self, node, indent)
+        }
+
+        fn heading(
+            &self,
+            tag: &Token,
+            content: &[InlineNode],
+            span: &Span,
+            indent: usize,
+        ) -> String {
+            format!(
+                r#"{}<{}{}{}>{}</{}>"#,
+                " ".repeat(indent),
+                tag.value(),
+                create_class_attribute(*tag, self.options()),
+                create_source_line_attribute(span, self.options()),
+                self.inline(content),
+                tag.value()
+            )
+        }
+
+        fn paragraph(&self, content: &[InlineNode], span: &Span, indent: usize) -> String {
+            format!(
+                r#"{}<p{}{}>{}</p>"#,
+                " ".repeat(indent),
+                create_class_attribute(Token::P, self.options()),
+                create_source_line_attribute(span, self.options()),
+                self.inline(content)
+            )
+        }
+
+        /// Render `ul` or `ol` given as `tag`. `start` is the number of the first item of `ol`,
+        /// and paragraphs in the items of `loose` list are wrapped with `p`.
+        fn list(
+            &self,
+            tag: &Token,
+            items: &[ElementNode],
+            start: Option<u32>,
+            loose: bool,
+            span: &Span,
+            indent: usize,
+        ) -> String {
+            let whitespace = " ".repeat(indent);
+            format!(
+                r#"{}<{}{}{}{}>
+{}{}</{}>"#,
+                whitespace,
+                tag.value(),
+                create_class_attribute(*tag, self.options()),
+                match start {
+                    Some(start) if start != 1 => format!(r#" start="{}""#, start),
+                    _ => "".to_string(),
+                },
+                create_source_line_attribute(span, self.options()),
+                items
+                    .iter()
+                    .map(|item| format!("{}\n", render_list_item(self, item, loose, indent + 2)))
+                    .collect::<String>(),
+                whitespace,
+                tag.value()
+            )
+        }
+
+        /// Render list item holding `children` blocks, whose paragraphs are
+        /// wrapped with `p` only in `loose` list.
+        ///
+        /// Task list item, whose `checked` is `Some`, starts with a checkbox.
+        fn list_item(
+            &self,
+            children: &[ElementNode],
+            checked: Option<bool>,
+            loose: bool,
+            span: &Span,
+            indent: usize,
+        ) -> String {
+            let whitespace = " ".repeat(indent);
+            let mut classes = create_classes(Token::Li, self.options());
+            if checked.is_some() {
+                classes.push(create_class_name("task", self.options()));
+            }
+            let attributes = format!(
+                r#" class="{}"{}"#,
+                classes.join(" "),
+                create_source_line_attribute(span, self.options())
+            );
+            let mut head: Vec<String> = checked
+                .map(|checked| create_task_checkbox(checked, span, self.options()))
+                .into_iter()
+                .collect();
+            // paragraph at the head of tight item is on the line of `li`
+            let rest = match children.split_first() {
+                Some((
+                    ElementNode::Exist {
+                        tag: Token::P,
+                        content,
+                        ..
+                    },
+                    rest,
+                )) if !loose => {
+                    head.push(self.inline(inline_nodes(content)));
+                    rest
+                }
+                _ => children,
+            };
+            if rest.is_empty() {
+                return format!("{}<li{}>{}</li>", whitespace, attributes, head.join(" "));
+            }
+            let body: String = rest
+                .iter()
+                .map(|child| match child {
+                    ElementNode::Exist {
+                        tag: Token::P,
+                        content,
+                        ..
+                    } if !loose => format!(
+                        "{}{}\n",
+                        " ".repeat(indent + 2),
+                        self.inline(inline_nodes(content))
+                    ),
+                    _ => match self.element(child, indent + 2) {
+                        html if html.is_empty() => html,
+                        html => format!("{}\n", html),
+                    },
+                })
+                .collect();
+            format!(
+                "{}<li{}>{}\n{}{}</li>",
+                whitespace,
+                attributes,
+                head.join(" "),
+                body,
+                whitespace
+            )
+        }
+
+        fn blockquote(&self, children: &[ElementNode], span: &Span, indent: usize) -> String {
+            let whitespace = " ".repeat(indent);
+            format!(
+                r#"{}<blockquote{}{}>
+{}{}</blockquote>"#,
+                whitespace,
+                create_class_attribute(Token::Blockquote, self.options()),
+                create_source_line_attribute(span, self.options()),
+                self.nested_elements(children, indent + 2),
+                whitespace
+            )
+        }
+
+        /// Render fenced code block, whose `language` is the first word of the info string
+        /// and `meta` is the rest of it.
+        #[allow(unused_variables)]
+        fn code_block(
+            &self,
+            code: &str,
+            language: Option<&str>,
+            meta: Option<&str>,
+            span: &Span,
+            indent: usize,
+        ) -> String {
+            let mut classes = create_classes(Token::Code, self.options());
+            if let Some(language) = language {
+                classes.push(format!("language-{}", language));
+            }
+            // whitespace in `pre` is the code itself, so it is not indented
+            format!(
+                r#"{}<pre{}><code class="{}">{}</code></pre>"#,
+                " ".repeat(indent),
+                create_source_line_attribute(span, self.options()),
+                escape_code_string(&classes.join(" ")),
+                match code.is_empty() {
+                    true => String::new(),
+                    false => format!("{}\n", highlight_code(code, language, self.options())),
+                }
+            )
+        }
+
+        /// Render `---`, `***` or `___` as `hr`.
+        fn thematic_break(&self, span: &Span, indent: usize) -> String {
+            format!(
+                "{}<hr{}{}>",
+                " ".repeat(indent),
+                create_class_attribute(Token::Hr, self.options()),
+                create_source_line_attribute(span, self.options())
+            )
+        }
+
+        /// Render html block such as `<div>`, which is a paragraph of escaped text
+        /// unless [`RenderOptions::allow_raw_html`] is set.
+        ///
+        /// Returns empty string when the sanitizer removes the whole block.
+        fn html_block(&self, html: &str, span: &Span, indent: usize) -> String {
+            if !self.options().allow_raw_html {
+                let value = html.to_string();
+                return self.paragraph(&[InlineNode::Html { value, span: *span }], span, indent);
+            }
+            match create_raw_html(html, self.options()) {
+                html if html.trim().is_empty() => String::new(),
+                html => format!("{}{}", " ".repeat(indent), html),
+            }
+        }
+
+        fn table(&self, table: &Table, span: &Span, indent: usize) -> String {
+            let whitespace = " ".repeat(indent);
+            format!(
+                r#"{}<table{}{}>
+{}
+{}
+{}</table>"#,
+                whitespace,
+                create_class_attribute(Token::Table, self.options()),
+                create_source_line_attribute(span, self.options()),
+                create_thead(&table.head, indent, self.options(), |cell| {
+                    self.inline(cell)
+                }),
+                create_tbody(&table.head, &table.body, indent, self.options(), |cell| {
+                    self.inline(cell)
+                }),
+                whitespace
+            )
+        }
+
+        /// Render each of `nodes` followed by a newline.
+        fn nested_elements(&self, nodes: &[ElementNode], indent: usize) -> String {
+            nodes
+                .iter()
+                .map(|node| self.element(node, indent))
+                .filter(|html| !html.is_empty())
+                .map(|html| format!("{}\n", html))
+                .collect()
+        }
+
+        /// Render `nodes` by calling the method for each kind.
+        fn inline(&self, nodes: &[InlineNode]) -> String {
+            let options = self.options();
+            let sanitized = match &options.html_sanitizer {
+                Some(sanitizer) if options.allow_raw_html => sanitizer.sanitize_inline(nodes),
+                _ => nodes.to_vec(),
+            };
+            sanitized
+                .iter()
+                .map(|node| match node {
+                    InlineNode::Text { value, .. } => self.text(value),
+                    InlineNode::Emphasis { children, .. } => self.emphasis(children),
+                    InlineNode::Strong { children, .. } => self.strong(children),
+                    InlineNode::Code { value, .. } => self.code(value),
+                    InlineNode::Link {
+                        href,
+                        title,
+                        children,
+                        ..
+                    } => self.link(href, title.as_deref(), children),
+                    InlineNode::Image {
+                        src, alt, title, ..
+                    } => self.image(src, alt, title.as_deref()),
+                    InlineNode::SoftBreak { .. } => self.soft_break(),
+                    InlineNode::HardBreak { .. } => self.hard_break(),
+                    InlineNode::Html { value, .. } => self.html(value),
+                })
+                .collect()
+        }
+
+        /// Render text, which is always escaped.
+        fn text(&self, value: &str) -> String {
+            escape_code_string(value)
+        }
+
+        /// Render raw html, which is escaped unless [`RenderOptions::allow_raw_html`] is set.
+        fn html(&self, value: &str) -> String {
+            let options = self.options();
+            match &options.html_sanitizer {
+                _ if !options.allow_raw_html => escape_code_string(value),
+                Some(sanitizer) => sanitizer.sanitize_tag(value, &options.url_policy),
+                None => value.to_string(),
+            }
+        }
+
+        fn emphasis(&self, children: &[InlineNode]) -> String {
+            format!(
+                r#"<em{}>{}</em>"#,
+                create_class_attribute(HtmlTag::Em, self.options()),
+                self.inline(children)
+            )
+        }
+
+        fn strong(&self, children: &[InlineNode]) -> String {
+            format!(
+                r#"<strong{}>{}</strong>"#,
+                create_class_attribute(HtmlTag::Strong, self.options()),
+                self.inline(children)
+            )
+        }
+
+        fn code(&self, value: &str) -> String {
+            format!(
+                r#"<code{}>{}</code>"#,
+                create_class_attribute(HtmlTag::InlineCode, self.options()),
+                escape_code_string(value)
+            )
+        }
+
+        fn soft_break(&self) -> String {
+            "\n".to_string()
+        }
+
+        fn hard_break(&self) -> String {
+            format!(
+                r#"<br{} />
+"#,
+                create_class_attribute(HtmlTag::Br, self.options())
+            )
+        }
+
+        /// Render link. `href` is dropped when [`RenderOptions::url_policy`] rejects it.
+        fn link(&self, href: &str, title: Option<&str>, children: &[InlineNode]) -> String {
+            format!(
+                r#"<a{}{} alt="{}"{}{}>{}</a>"#,
+                create_class_attribute(HtmlTag::A, self.options()),
+                create_url_attribute("href", href, self.options()),
+                escape_code_string(&text_content(children)),
+                create_title_attribute(title),
+                create_external_link_attribute(href, self.options()),
+                self.inline(children)
+            )
+        }
+
+        /// Render image. `src` is dropped when [`RenderOptions::url_policy`] rejects it.
+        fn image(&self, src: &str, alt: &str, title: Option<&str>) -> String {
+            format!(
+                r#"<img{}{} alt="{}"{}>"#,
+                create_class_attribute(HtmlTag::Img, self.options()),
+                create_url_attribute("src", src, self.options()),
+                escape_code_string(alt),
+                create_title_attribute(title)
+            )
+        }
+    }
+
+    /// Renderer for flav md html, which is used by [`MdNode::to_html_string`].
+    #[derive(Debug, Clone, Default)]
+    pub struct FlavHtmlRenderer {
+        options: RenderOptions,
+    }
+
+    impl FlavHtmlRenderer {
+        pub fn new(options: RenderOptions) -> Self {
+            FlavHtmlRenderer { options }
+        }
+    }
+
+    impl Renderer for FlavHtmlRenderer {
+        fn options(&self) -> &RenderOptions {
+            &self.options
+        }
+    }
+
+    /// Inline nodes of `content`, which is empty for other kinds.
+    fn inline_nodes(content: &Content) -> &[InlineNode] {
+        match content {
+            Content::InlineNodes { value } => value,
+            _ => &[],
+        }
+    }
+
+    /// Render `item` of the list, which is `loose` or not.
+    fn render_list_item<R: Renderer + ?Sized>(
+        renderer: &R,
+        item: &ElementNode,
+        loose: bool,
+        indent: usize,
+    ) -> String {
+        match item {
+            ElementNode::Exist {
+                tag: Token::Li,
+                content,
+                span,
+                ..
+            } => match &**content {
+                Content::ListItem { value, checked } => {
+                    renderer.list_item(value, *checked, loose, span, indent)
+                }
+                _ => renderer.list_item(&[], None, loose, span, indent),
+            },
+            _ => renderer.element(item, indent),
+        }
+    }
+
+    fn element_nodes(content: &Content) -> &[ElementNode] {
+        match content {
+            Content::ElementNodes { value } => value,
+            _ => &[],
+        }
+    }
+
+    fn render_element<R: Renderer + ?Sized>(
+        renderer: &R,
+        node: &ElementNode,
+        indent: usize,
+    ) -> String {
+        let (tag, content, span) = match node {
+            ElementNode::Exist {
+                tag, content, span, ..
+            } => (tag, &**content, span),
+            ElementNode::Nil => return "".to_string(),
+        };
+        match tag {
+            tag if tag.is_head() => renderer.heading(tag, inline_nodes(content), span, indent),
+            Token::Ul | Token::Ol => match content {
+                Content::List {
+                    value,
+                    start,
+                    loose,
+                } => renderer.list(tag, value, *start, *loose, span, indent),
+                _ => renderer.list(tag, element_nodes(content), None, false, span, indent),
+            },
+            Token::Li => render_list_item(renderer, node, false, indent),
+            Token::Blockquote => renderer.blockquote(element_nodes(content), span, indent),
+            Token::Code => match content {
+                Content::CodeBlock {
+                    value,
+                    language,
+                    meta,
+                } => match language.as_deref().and_then(|language| {
+                    renderer
+                        .options()
+                        .code_block_handlers
+                        .handle(language, value, meta.as_deref())
+                }) {
+                    Some(CodeBlockOutput::Html(html)) => format!("{}{}", " ".repeat(indent), html),
+                    Some(CodeBlockOutput::ElementNodes(nodes)) => nodes
+                        .iter()
+                        .map(|node| renderer.element(node, indent))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                    None => renderer.code_block(
+                        value,
+                        language.as_deref(),
+                        meta.as_deref(),
+                        span,
+                        indent,
+                    ),
+                },
+                _ => "".to_string(),
+            },
+            Token::Table => match content {
+                Content::Table { value } => renderer.table(value, span, indent),
+                _ => "".to_string(),
+            },
+            Token::Hr => renderer.thematic_break(span, indent),
+            Token::Html => renderer.html_block(&text_content(inline_nodes(content)), span, indent),
+            _ => renderer.paragraph(inline_nodes(content), span, indent),
+        }
+    }
+
+    fn create_source_line_attribute(span: &Span, options: &RenderOptions) -> String {
+        // nodes built by hand have no position in the source
+        if !options.source_line || span.start.line == 0 {
+            return "".to_string();
+        }
+        if options.source_line_end {
+            format!(
+                r#" data-source-line="{}" data-source-line-end="{}""#,
+                span.start.line, span.end.line
+            )
+        } else {
+            format!(r#" data-source-line="{}""#, span.start.line)
+        }
+    }
+
+    /// `value` which may contain html, escaped unless [`RenderOptions::allow_raw_html`] is set.
+    fn create_raw_html(value: &str, options: &RenderOptions) -> String {
+        match &options.html_sanitizer {
+            _ if !options.allow_raw_html => escape_code_string(value),
+            Some(sanitizer) => sanitizer.sanitize(value, &options.url_policy),
+            None => value.to_string(),
+        }
+    }
+
+    fn create_class_name(name: &str, options: &RenderOptions) -> String {
+        format!("{}-{}", options.class_prefix, name)
+    }
+
+    fn generate_class_for_the_tag(tag: HtmlTag, options: &RenderOptions) -> Vec<String> {
+        let text = create_class_name("text", options);
+        match tag {
+            HtmlTag::Block(
+                tag @ (Token::H1 | Token::H2 | Token::H3 | Token::H4 | Token::H5 | Token::H6),
+            ) => vec![
+                text,
+                create_class_name(&tag.value(), options),
+                create_class_name("h", options),
+            ],
+            HtmlTag::Block(Token::P) => vec![text, create_class_name("p", options)],
+            HtmlTag::Block(Token::Blockquote) => {
+                vec![text, create_class_name("blockquote", options)]
+            }
+            HtmlTag::Block(Token::Li) => vec![text, create_class_name("li", options)],
+            HtmlTag::Block(tag @ (Token::Ul | Token::Ol)) => {
+                vec![create_class_name(&tag.value(), options)]
+            }
+            HtmlTag::Block(Token::Code) => vec![create_class_name("code", options)],
+            HtmlTag::Block(Token::Hr) => vec![create_class_name("hr", options)],
+            HtmlTag::Em => vec![create_class_name("em", options)],
+            HtmlTag::Strong => vec![create_class_name("strong", options)],
+            HtmlTag::InlineCode => vec![create_class_name("code-inline", options)],
+            HtmlTag::A => vec![create_class_name("a", options)],
+            HtmlTag::Img => vec![create_class_name("img", options)],
+            HtmlTag::Br => vec![create_class_name("br", options)],
+            HtmlTag::Block(Token::Table | Token::Html)
+            | HtmlTag::Thead
+            | HtmlTag::Tbody
+            | HtmlTag::Tr
+            | HtmlTag::Th
+            | HtmlTag::Td => vec![],
+        }
+    }
+
+    /// Classes of `tag` including extra classes.
+    fn create_classes(tag: impl Into<HtmlTag>, options: &RenderOptions) -> Vec<String> {
+        let tag = tag.into();
+        let mut classes = generate_class_for_the_tag(tag, options);
+        if let Some(extra) = options.extra_classes_per_tag.get(&tag) {
+            classes.extend(extra.iter().cloned());
+        }
+        classes
+    }
+
+    /// `class` attribute of `tag` including extra classes, or empty when it has no class.
+    fn create_class_attribute(tag: impl Into<HtmlTag>, options: &RenderOptions) -> String {
+        let classes = create_classes(tag, options);
+        if classes.is_empty() {
+            "".to_string()
+        } else {
+            format!(r#" class="{}""#, classes.join(" "))
+        }
+    }
+
+    /// Escaped `code`, highlighted when the `highlight` feature is enabled and `language` is known.
+    #[allow(unused_variables)]
+    fn highlight_code(code: &str, language: Option<&str>, options: &RenderOptions) -> String {
+        #[cfg(feature = "highlight")]
+        if let Some(html) = language.and_then(|language| {
+            crate::lexer::highlight::highlight::highlight(code, language, &options.class_prefix)
+        }) {
+            return html;
+        }
+        escape_code_string(code)
+    }
+
+    /// Checkbox of task list item, which is disabled unless `task_checkbox_enabled` is set.
+    fn create_task_checkbox(checked: bool, span: &Span, options: &RenderOptions) -> String {
+        let state = match options.task_checkbox_enabled {
+            true => format!(r#" data-source-line="{}""#, span.start.line),
+            false => " disabled".to_string(),
+        };
+        format!(
+            r#"<input type="checkbox" class="{}"{}{} />"#,
+            create_class_name("checkbox", options),
+            state,
+            match checked {
+                true => " checked",
+                false => "",
+            }
+        )
+    }
+
+    fn create_title_attribute(title: Option<&str>) -> String {
+        match title {
+            Some(title) => format!(r#" title="{}""#, escape_code_string(title)),
+            None => "".to_string(),
+        }
+    }
+
+    fn create_url_attribute(name: &str, url: &str, options: &RenderOptions) -> String {
+        match options.url_policy.sanitize(url) {
+            Some(url) => format!(r#" {}="{}""#, name, escape_code_string(url)),
+            None => "".to_string(),
+        }
+    }
+
+    /// `rel` and `target` for links to other sites.
+    fn create_external_link_attribute(href: &str, options: &RenderOptions) -> String {
+        let policy = &options.url_policy;
+        if !policy.is_external(href) || policy.sanitize(href).is_none() {
+            return "".to_string();
+        }
+        let mut attribute = "".to_string();
+        if policy.rel_nofollow {
+            attribute += r#" rel="nofollow noopener""#;
+        }
+        if let Some(target) = &policy.external_target {
+            attribute += &format!(r#" target="{}""#, escape_code_string(target));
+        }
+        attribute
+    }
+
+    fn create_thead(
+        head: &[TableHead],
+        indent: usize,
+        options: &RenderOptions,
+        render_cell: impl Fn(&[InlineNode]) -> String,
+    ) -> String {
+        let whitespace = " ".repeat(indent);
+        let heads = head
+            .iter()
+            .map(|item| {
+                format!(
+                    "{}      <th{} style=\"text-align: {}\">{}</th>",
+                    whitespace,
+                    create_class_attribute(HtmlTag::Th, options),
+                    item.get_align(),
+                    render_cell(&item.cell)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        format!(
+            r#"{0}  <thead{1}>
+{0}    <tr{2}>
+{3}
+{0}    </tr>
+{0}  </thead>"#,
+            whitespace,
+            create_class_attribute(HtmlTag::Thead, options),
+            create_class_attribute(HtmlTag::Tr, options),
+            heads
+        )
+    }
+
+    fn create_tbody(
+        head: &[TableHead],
+        body: &[TableRow],
+        indent: usize,
+        options: &RenderOptions,
+        render_cell: impl Fn(&[InlineNode]) -> String,
+    ) -> String {
+        let whitespace = " ".repeat(indent);
+        let trs = body
+            .iter()
+            .map(|rows| {
+                let tds = rows
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        format!(
+                            "{}      <td{} style=\"text-align: {}\">{}</td>",
+                            whitespace,
+                            create_class_attribute(HtmlTag::Td, options),
+                            // cells beyond the head are left aligned
+                            head.get(i).map_or(Align::Left, TableHead::align).value(),
+                            render_cell(item)
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                format!(
+                    r#"{0}    <tr{1}>
+{2}
+{0}    </tr>"#,
+                    whitespace,
+                    create_class_attribute(HtmlTag::Tr, options),
+                    tds
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        format!(
+            r#"{0}  <tbody{1}>
+{2}
+{0}  </tbody>"#,
+            whitespace,
+            create_class_attribute(HtmlTag::Tbody, options),
+            trs
+        )
+    }
+
+    #[cfg(test)]
+    mod test_renderer {
+        use super::*;
+        use crate::lexer::lexer::lexer::Lexer;
+        use crate::{inline_text, vec_string};
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_create_inline() {
+            let input = vec![
+                InlineNode::Link {
+                    href: "https://example.com".to_string(),
+                    title: Some("hoge".to_string()),
+                    children: vec![
+                        InlineNode::Strong {
+                            children: vec![inline_text!("Google")],
+                            span: Default::default(),
+                        },
+                        inline_text!("先生"),
+                    ],
+                    span: Default::default(),
+                },
+                InlineNode::Image {
+                    src: "a.png".to_string(),
+                    alt: "fuga".to_string(),
+                    title: None,
+                    span: Default::default(),
+                },
+            ];
+            let expected = r#"<a class="flav-md-a" href="https://example.com" alt="Google先生" title="hoge"><strong class="flav-md-strong">Google</strong>先生</a><img class="flav-md-img" src="a.png" alt="fuga">"#;
+            assert_eq!(FlavHtmlRenderer::default().inline(&input), expected);
+        }
+
+        #[test]
+        fn test_source_line() {
+            let md_node = Lexer::new(vec_string![
+                "# hello", "", "* hoge", "  * fuga", "> quote", "", "```", "code", "```", "|a|",
+                "|-|",
+            ])
+            .parse();
+            let expected = r#"<h1 class="flav-md-text flav-md-h1 flav-md-h" data-source-line="1">hello</h1>
+<ul class="flav-md-ul" data-source-line="3">
+  <li class="flav-md-text flav-md-li" data-source-line="3">hoge
+    <ul class="flav-md-ul" data-source-line="4">
+      <li class="flav-md-text flav-md-li" data-source-line="4">fuga</li>
+    </ul>
+  </li>
+</ul>
+<blockquote class="flav-md-text flav-md-blockquote" data-source-line="5">
+  <p class="flav-md-text flav-md-p" data-source-line="5">quote</p>
+</blockquote>
+<pre data-source-line="7"><code class="flav-md-code">code
+</code></pre>
+<table data-source-line="10">
+  <thead>
+    <tr>
+      <th style="text-align: left">a</th>
+    </tr>
+  </thead>
+  <tbody>
+
+  </tbody>
+</table>"#;
+            let options = RenderOptions {
+                source_line: true,
+                ..Default::default()
+            };
+            assert_eq!(md_node.to_html_string_with(&options), expected);
+        }
+
+        #[test]
+        fn test_source_line_end() {
+            let md_node =
+                Lexer::new(vec_string!["* hoge", "  * fuga", "", "```", "code", "```"]).parse();
+            let expected = r#"<ul class="flav-md-ul" data-source-line="1" data-source-line-end="2">
+  <li class="flav-md-text flav-md-li" data-source-line="1" data-source-line-end="2">hoge
+    <ul class="flav-md-ul" data-source-line="2" data-source-line-end="2">
+      <li class="flav-md-text flav-md-li" data-source-line="2" data-source-line-end="2">fuga</li>
+    </ul>
+  </li>
+</ul>
+<pre data-source-line="4" data-source-line-end="6"><code class="flav-md-code">code
+</code></pre>"#;
+            let options = RenderOptions {
+                source_line: true,
+                source_line_end: true,
+                ..Default::default()
+            };
+            assert_eq!(md_node.to_html_string_with(&options), expected);
+        }
+
+        #[test]
+        fn test_override_renderer() {
+            struct PlainCodeRenderer;
+
+            impl Renderer for PlainCodeRenderer {
+                fn code_block(
+                    &self,
+                    code: &str,
+                    language: Option<&str>,
+                    _meta: Option<&str>,
+                    _span: &Span,
+                    _indent: usize,
+                ) -> String {
+                    format!("<pre lang=\"{}\">{}</pre>", language.unwrap_or(""), code)
+                }
+
+                fn strong(&self, children: &[InlineNode]) -> String {
+                    format!("<b>{}</b>", self.inline(children))
+                }
+            }
+
+            let md_node = Lexer::new(vec_string!["# **hello**", "```c", "code", "```"]).parse();
+            let expected = r#"<h1 class="flav-md-text flav-md-h1 flav-md-h"><b>hello</b></h1>
+<pre lang="c">code</pre>"#;
+            assert_eq!(md_node.render(&PlainCodeRenderer), expected);
+        }
+
+        #[test]
+        fn test_class_prefix_and_extra_classes() {
+            let md_node = Lexer::new(vec_string![
+                "# *hello*",
+                "* hoge",
+                "```rust",
+                "code",
+                "```",
+                "|a|",
+                "|-|",
+                "|1|"
+            ])
+            .parse();
+            let expected = r#"<h1 class="docs-md-text docs-md-h1 docs-md-h title"><em class="docs-md-em italic">hello</em></h1>
+<ul class="docs-md-ul">
+  <li class="docs-md-text docs-md-li">hoge</li>
+</ul>
+<pre><code class="docs-md-code language-rust">code
+</code></pre>
+<table class="table is-striped">
+  <thead>
+    <tr class="row">
+      <th class="cell" style="text-align: left">a</th>
+    </tr>
+  </thead>
+  <tbody>
+    <tr class="row">
+      <td class="cell" style="text-align: left">1</td>
+    </tr>
+  </tbody>
+</table>"#;
+            let options = RenderOptions {
+                class_prefix: "docs-md".to_string(),
+                extra_classes_per_tag: HashMap::from([
+                    (Token::H1.into(), vec_string!["title"]),
+                    (Token::Table.into(), vec_string!["table", "is-striped"]),
+                    (HtmlTag::Em, vec_string!["italic"]),
+                    (HtmlTag::Tr, vec_string!["row"]),
+                    (HtmlTag::Th, vec_string!["cell"]),
+                    (HtmlTag::Td, vec_string!["cell"]),
+                ]),
+                ..Default::default()
+            };
+            assert_eq!(md_node.to_html_string_with(&options), expected);
+        }
+
+        #[test]
+        fn test_escape_text() {
+            let md_node = Lexer::new(vec_string![
+                "# <script>alert('x')</script>",
+                "* a & b",
+                "",
+                r#"`<b>` [<i>](url "<x>") ![<img>](a.png)"#,
+                "|<td>|",
+                "|-|",
+                "|<br>|",
+            ])
+            .parse();
+            let expected = r#"<h1 class="flav-md-text flav-md-h1 flav-md-h">&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;</h1>
+<ul class="flav-md-ul">
+  <li class="flav-md-text flav-md-li">a &amp; b</li>
+</ul>
+<p class="flav-md-text flav-md-p"><code class="flav-md-code-inline">&lt;b&gt;</code> <a class="flav-md-a" href="url" alt="&lt;i&gt;" title="&lt;x&gt;">&lt;i&gt;</a> <img class="flav-md-img" src="a.png" alt="&lt;img&gt;"></p>
+<table>
+  <thead>
+    <tr>
+      <th style="text-align: left">&lt;td&gt;</th>
+    </tr>
+  </thead>
+  <tbody>
+    <tr>
+      <td style="text-align: left">&lt;br&gt;</td>
+    </tr>
+  </tbody>
+</table>"#;
+            assert_eq!(md_node.to_html_string(), expected);
+        }
+
+        #[test]
+        fn test_allow_raw_html() {
+            let md_node = Lexer::new(vec_string!["a <b>bold</b> `<b>`"]).parse();
+            let options = RenderOptions {
+                allow_raw_html: true,
+                ..Default::default()
+            };
+            assert_eq!(
+                md_node.to_html_string_with(&options),
+                r#"<p class="flav-md-text flav-md-p">a <b>bold</b> <code class="flav-md-code-inline">&lt;b&gt;</code></p>"#
+            );
+
+            let md_node =
+                Lexer::new(vec_string![r#"<span title="a *b* c">_x_</span> \<i>"#]).parse();
+            assert_eq!(
+                md_node.to_html_string_with(&options),
+                r#"<p class="flav-md-text flav-md-p"><span title="a *b* c"><em class="flav-md-em">x</em></span> &lt;i&gt;</p>"#
+            );
+        }
+
+        #[test]
+        fn test_html_block() {
+            let md_node = Lexer::new(vec_string![
+                "a",
+                r#"<div class="note">"#,
+                "*b*",
+                "",
+                "*c*",
+                "</div>",
+            ])
+            .parse();
+            let options = RenderOptions {
+                allow_raw_html: true,
+                ..Default::default()
+            };
+            let expected = r#"<p class="flav-md-text flav-md-p">a</p>
+<div>
+*b*</div>
+<p class="flav-md-text flav-md-p"><em class="flav-md-em">c</em></p>"#;
+            assert_eq!(md_node.to_html_string_with(&options), expected);
+
+            let removed = Lexer::new(vec_string![
+                "<script>alert(1)</script>",
+                "> <style>p {}</style>",
+                "> d",
+                "",
+                "e",
+            ])
+            .parse();
+            let expected = r#"<blockquote class="flav-md-text flav-md-blockquote">
+  <p class="flav-md-text flav-md-p">d</p>
+</blockquote>
+<p class="flav-md-text flav-md-p">e</p>"#;
+            assert_eq!(removed.to_html_string_with(&options), expected);
+
+            let expected = r#"<p class="flav-md-text flav-md-p">a</p>
+<p class="flav-md-text flav-md-p">&lt;div class=&quot;note&quot;&gt;
+*b*</p>
+<p class="flav-md-text flav-md-p"><em class="flav-md-em">c</em></p>
+<p class="flav-md-text flav-md-p">&lt;/div&gt;</p>"#;
+            assert_eq!(md_node.to_html_string(), expected);
+        }
+
+        #[test]
+        fn test_line_break() {
+            let md_node = Lexer::new(vec_string!["a", "b  ", "c"]).parse();
+            let expected = r#"<p class="flav-md-text flav-md-p">a
+b<br class="flav-md-br" />
+c</p>"#;
+            assert_eq!(md_node.to_html_string(), expected);
+        }
+
+        #[test]
+        fn test_thematic_break() {
+            let md_node = Lexer::new(vec_string!["a", "", "---", "> * * *"]).parse();
+            let expected = r#"<p class="flav-md-text flav-md-p">a</p>
+<hr class="flav-md-hr">
+<blockquote class="flav-md-text flav-md-blockquote">
+  <hr class="flav-md-hr">
+</blockquote>"#;
+            assert_eq!(md_node.to_html_string(), expected);
+        }
+
+        #[test]
+        fn test_nested_table() {
+            let md_node = Lexer::new(vec_string!["> |a|", "> |-|", "> |1|"]).parse();
+            let expected = r#"<blockquote class="flav-md-text flav-md-blockquote">
+  <table>
+    <thead>
+      <tr>
+        <th style="text-align: left">a</th>
+      </tr>
+    </thead>
+    <tbody>
+      <tr>
+        <td style="text-align: left">1</td>
+      </tr>
+    </tbody>
+  </table>
+</blockquote>"#;
+            assert_eq!(md_node.to_html_string(), expected);
+        }
+
+        #[test]
+        fn test_table_inline() {
+            let md_node = Lexer::new(vec_string![
+                "| *a* | `b \\| c` |",
+                "|---|---|",
+                "| [d](e) | **f** |",
+            ])
+            .parse();
+            let expected = r#"<table>
+  <thead>
+    <tr>
+      <th style="text-align: left"><em class="flav-md-em">a</em></th>
+      <th style="text-align: left"><code class="flav-md-code-inline">b | c</code></th>
+    </tr>
+  </thead>
+  <tbody>
+    <tr>
+      <td style="text-align: left"><a class="flav-md-a" href="e" alt="d">d</a></td>
+      <td style="text-align: left"><strong class="flav-md-strong">f</strong></td>
+    </tr>
+  </tbody>
+</table>"#;
+            assert_eq!(md_node.to_html_string(), expected);
+        }
+
+        #[test]
+        fn test_list_start() {
+            let md_node = Lexer::new(vec_string!["3) a", "4) b"]).parse();
+            let expected = r#"<ol class="flav-md-ol" start="3">
+  <li class="flav-md-text flav-md-li">a</li>
+  <li class="flav-md-text flav-md-li">b</li>
+</ol>"#;
+            assert_eq!(md_node.to_html_string(), expected);
+        }
+
+        #[test]
+        fn test_list_item_blocks() {
+            let md_node =
+                Lexer::new(vec_string!["- a", "  > b", "- c", "", "1. d", "", "   e"]).parse();
+            let expected = r#"<ul class="flav-md-ul">
+  <li class="flav-md-text flav-md-li">a
+    <blockquote class="flav-md-text flav-md-blockquote">
+      <p class="flav-md-text flav-md-p">b</p>
+    </blockquote>
+  </li>
+  <li class="flav-md-text flav-md-li">c</li>
+</ul>
+<ol class="flav-md-ol">
+  <li class="flav-md-text flav-md-li">
+    <p class="flav-md-text flav-md-p">d</p>
+    <p class="flav-md-text flav-md-p">e</p>
+  </li>
+</ol>"#;
+            assert_eq!(md_node.to_html_string(), expected);
+        }
+
+        #[test]
+        fn test_task_list() {
+            let md_node = Lexer::new(vec_string!["- [ ] a", "- [x] *b*", "- c"]).parse();
+            let expected = r#"<ul class="flav-md-ul">
+  <li class="flav-md-text flav-md-li flav-md-task"><input type="checkbox" class="flav-md-checkbox" disabled /> a</li>
+  <li class="flav-md-text flav-md-li flav-md-task"><input type="checkbox" class="flav-md-checkbox" disabled checked /> <em class="flav-md-em">b</em></li>
+  <li class="flav-md-text flav-md-li">c</li>
+</ul>"#;
+            assert_eq!(md_node.to_html_string(), expected);
+
+            let options = RenderOptions {
+                task_checkbox_enabled: true,
+                ..Default::default()
+            };
+            let expected = r#"<ul class="flav-md-ul">
+  <li class="flav-md-text flav-md-li flav-md-task"><input type="checkbox" class="flav-md-checkbox" data-source-line="1" /> a</li>
+  <li class="flav-md-text flav-md-li flav-md-task"><input type="checkbox" class="flav-md-checkbox" data-source-line="2" checked /> <em class="flav-md-em">b</em></li>
+  <li class="flav-md-text flav-md-li">c</li>
+</ul>"#;
+            assert_eq!(md_node.to_html_string_with(&options), expected);
+        }
+
+        #[test]
+        fn test_sanitize_raw_html() {
+            let md_node = Lexer::new(vec_string![
+                r#"<div onclick="alert(1)">"#,
+                "a<script>alert(1)</script> <img src=x onerror=alert(1)>",
+                r#"<a href="javascript:alert(1)">*b*</a>"#,
+            ])
+            .parse();
+            let options = RenderOptions {
+                allow_raw_html: true,
+                ..Default::default()
+            };
+            let expected = r#"<div>
+a <img src="x">
+<a>*b*</a></div>"#;
+            assert_eq!(md_node.to_html_string_with(&options), expected);
+
+            let options = RenderOptions {
+                allow_raw_html: true,
+                html_sanitizer: None,
+                ..Default::default()
+            };
+            assert!(md_node
+                .to_html_string_with(&options)
+                .contains("<script>alert(1)</script>"));
+
+            let md_node = Lexer::new(vec_string![
+                "a <b>bold *c*",
+                "",
+                "<i>d *e</i> f* g</b>",
+                "",
+                "<span title=\"a *b* c\">",
+            ])
+            .parse();
+            let expected = r#"<p class="flav-md-text flav-md-p">a <b>bold <em class="flav-md-em">c</em></b></p>
+<p class="flav-md-text flav-md-p"><i>d <em class="flav-md-em">e f</em> g</i></p>
+<span title="a *b* c"></span>"#;
+            let options = RenderOptions {
+                allow_raw_html: true,
+                ..Default::default()
+            };
+            assert_eq!(md_node.to_html_string_with(&options), expected);
+        }
+
+        #[test]
+        fn test_url_policy() {
+            let md_node = Lexer::new(vec_string![
+                "[a](javascript:alert(1)) ![b](data:image/png;base64,AAAA)",
+                r#"[c](https://example.com/?a=1&b="2") [d](/about)"#,
+            ])
+            .parse();
+            let expected = r#"<p class="flav-md-text flav-md-p"><a class="flav-md-a" alt="a">a</a> <img class="flav-md-img" alt="b">
+<a class="flav-md-a" href="https://example.com/?a=1&amp;b=&quot;2&quot;" alt="c">c</a> <a class="flav-md-a" href="/about" alt="d">d</a></p>"#;
+            assert_eq!(md_node.to_html_string(), expected);
+
+            let options = RenderOptions {
+                url_policy: UrlPolicy {
+                    rel_nofollow: true,
+                    external_target: Some("_blank".to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let expected = r#"<p class="flav-md-text flav-md-p"><a class="flav-md-a" alt="a">a</a> <img class="flav-md-img" alt="b">
+<a class="flav-md-a" href="https://example.com/?a=1&amp;b=&quot;2&quot;" alt="c" rel="nofollow noopener" target="_blank">c</a> <a class="flav-md-a" href="/about" alt="d">d</a></p>"#;
+            assert_eq!(md_node.to_html_string_with(&options), expected);
+        }
+    }
+}
@@ -0,0 +1,396 @@
+pub mod sanitizer {
+    use crate::lexer::lexer::lexer::InlineNode;
+    use crate::lexer::source::source::Span;
+    use crate::lexer::url_policy::url_policy::UrlPolicy;
+    use crate::util::string::string::escape_code_string;
+    use crate::vec_string;
+    use once_cell::sync::Lazy;
+    use regex::Regex;
+
+    static TAG_PATTERN: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r#"^<(/?)([a-zA-Z][a-zA-Z0-9-]*)((?:\s+[^\s"'>/=]+(?:\s*=\s*(?:"[^"]*"|'[^']*'|[^\s"'=<>`]+))?)*)\s*/?>"#,
+        )
+        .unwrap()
+    });
+
+    static ATTRIBUTE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#"([^\s"'>/=]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'=<>`]+)))?"#).unwrap()
+    });
+
+    static COMMENT_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^<!--[\s\S]*?(?:-->|$)").unwrap());
+
+    static ENTITY_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"&(?:#[xX]([0-9a-fA-F]+);?|#([0-9]+);?|([a-zA-Z]+);)").unwrap());
+
+    /// Tags whose content is dropped together with them.
+    const DROP_CONTENT_TAGS: [&str; 7] = [
+        "script", "style", "iframe", "object", "noscript", "textarea", "title",
+    ];
+
+    /// Tags which have no content and no end tag.
+    const VOID_TAGS: [&str; 13] = [
+        "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source",
+        "track", "wbr",
+    ];
+
+    /// Attributes whose value is url checked by [`UrlPolicy`].
+    const URL_ATTRIBUTES: [&str; 2] = ["href", "src"];
+
+    /// Sanitizer for html written in markdown, keeping only allowed tags and attributes.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct HtmlSanitizer {
+        /// Tags kept in lowercase. Other tags are dropped but their content is kept.
+        pub allowed_tags: Vec<String>,
+        /// Attributes kept on the allowed tags.
+        pub allowed_attributes: Vec<String>,
+    }
+
+    impl Default for HtmlSanitizer {
+        fn default() -> Self {
+            HtmlSanitizer {
+                allowed_tags: vec_string![
+                    "a",
+                    "abbr",
+                    "b",
+                    "blockquote",
+                    "br",
+                    "code",
+                    "dd",
+                    "del",
+                    "details",
+                    "div",
+                    "dl",
+                    "dt",
+                    "em",
+                    "h1",
+                    "h2",
+                    "h3",
+                    "h4",
+                    "h5",
+                    "h6",
+                    "hr",
+                    "i",
+                    "img",
+                    "ins",
+                    "kbd",
+                    "li",
+                    "mark",
+                    "ol",
+                    "p",
+                    "pre",
+                    "q",
+                    "s",
+                    "small",
+                    "span",
+                    "strong",
+                    "sub",
+                    "summary",
+                    "sup",
+                    "table",
+                    "tbody",
+                    "td",
+                    "th",
+                    "thead",
+                    "tr",
+                    "u",
+                    "ul",
+                ],
+                allowed_attributes: vec_string![
+                    "alt", "colspan", "height", "href", "rowspan", "src", "title", "width",
+                ],
+            }
+        }
+    }
+
+    impl HtmlSanitizer {
+        /// Sanitize `html`, escaping `<` and `>` which do not form a tag.
+        ///
+        /// `href` and `src` are dropped when `url_policy` rejects them.
+        /// Allowed tags left open are closed at the end, and end tags of
+        /// tags which are not open are dropped.
+        pub fn sanitize(&self, html: &str, url_policy: &UrlPolicy) -> String {
+            let mut rslt = String::new();
+            let mut open_tags = OpenTags::default();
+            let mut rest = html;
+            while let Some(at) = rest.find(['<', '>']) {
+                rslt.push_str(&rest[..at]);
+                rest = &rest[at..];
+                if rest.starts_with('>') {
+                    rslt.push_str("&gt;");
+                    rest = &rest[1..];
+                } else if let Some(comment) = COMMENT_PATTERN.find(rest) {
+                    rest = &rest[comment.end()..];
+                } else if let Some(caps) = TAG_PATTERN.captures(rest) {
+                    let name = caps[2].to_ascii_lowercase();
+                    rest = &rest[caps[0].len()..];
+                    if DROP_CONTENT_TAGS.contains(&name.as_str()) {
+                        if caps[1].is_empty() {
+                            rest = skip_to_end_tag(rest, &name);
+                        }
+                    } else if !self.allowed_tags.contains(&name) {
+                    } else if caps[1].is_empty() {
+                        rslt.push_str(&self.create_tag(&caps, url_policy));
+                        open_tags.open(name);
+                    } else {
+                        for name in open_tags.close(&name) {
+                            rslt.push_str(&format!("</{}>", name));
+                        }
+                    }
+                } else {
+                    rslt.push_str("&lt;");
+                    rest = &rest[1..];
+                }
+            }
+            rslt.push_str(rest);
+            for name in open_tags.close_all() {
+                rslt.push_str(&format!("</{}>", name));
+            }
+            rslt
+        }
+
+        /// Sanitize one raw html `tag`, dropping it when it is not allowed.
+        ///
+        /// Comments and declarations are always dropped.
+        pub fn sanitize_tag(&self, tag: &str, url_policy: &UrlPolicy) -> String {
+            match TAG_PATTERN.captures(tag) {
+                Some(caps) if caps[0].len() == tag.len() => self.create_tag(&caps, url_policy),
+                _ => "".to_string(),
+            }
+        }
+
+        /// `inline` without the content of tags such as `script`, whose
+        /// allowed tags are balanced as [`HtmlSanitizer::sanitize`] does.
+        ///
+        /// Raw html tags in the result are then sanitized one by one.
+        pub fn sanitize_inline(&self, inline: &[InlineNode]) -> Vec<InlineNode> {
+            let end_tag = |name: &str, span: &Span| InlineNode::Html {
+                value: format!("</{}>", name),
+                span: *span,
+            };
+            let mut rslt: Vec<InlineNode> = vec![];
+            let mut dropping: Option<String> = None;
+            let mut open_tags = OpenTags::default();
+            for node in inline {
+                let tag = match node {
+                    InlineNode::Html { value, .. } => TAG_PATTERN.captures(value),
+                    _ => None,
+                };
+                let (name, is_end) = match &tag {
+                    Some(caps) => (caps[2].to_ascii_lowercase(), !caps[1].is_empty()),
+                    None => ("".to_string(), false),
+                };
+                match &dropping {
+                    Some(dropped) => {
+                        if is_end && *dropped == name {
+                            dropping = None;
+                        }
+                    }
+                    None if DROP_CONTENT_TAGS.contains(&name.as_str()) => {
+                        if !is_end {
+                            dropping = Some(name);
+                        }
+                    }
+                    None if !self.allowed_tags.contains(&name) => rslt.push(node.clone()),
+                    None if is_end => {
+                        for name in open_tags.close(&name) {
+                            rslt.push(end_tag(&name, node.span()));
+                        }
+                    }
+                    None => {
+                        open_tags.open(name);
+                        rslt.push(node.clone());
+                    }
+                }
+            }
+            if let Some(last) = inline.last() {
+                let end = Span::new(last.span().end, last.span().end);
+                for name in open_tags.close_all() {
+                    rslt.push(end_tag(&name, &end));
+                }
+            }
+            rslt
+        }
+
+        /// Allowed tag of `caps` matched by `TAG_PATTERN` with its allowed attributes,
+        /// or empty when it is not allowed.
+        fn create_tag(&self, caps: &regex::Captures, url_policy: &UrlPolicy) -> String {
+            let name = caps[2].to_ascii_lowercase();
+            if !self.allowed_tags.contains(&name) || DROP_CONTENT_TAGS.contains(&name.as_str()) {
+                "".to_string()
+            } else if !caps[1].is_empty() {
+                format!("</{}>", name)
+            } else {
+                format!("<{}{}>", name, self.create_attributes(&caps[3], url_policy))
+            }
+        }
+
+        fn create_attributes(&self, input: &str, url_policy: &UrlPolicy) -> String {
+            let mut rslt = String::new();
+            for caps in ATTRIBUTE_PATTERN.captures_iter(input) {
+                let name = caps[1].to_ascii_lowercase();
+                if !self.allowed_attributes.contains(&name) {
+                    continue;
+                }
+                let value = decode_entities(
+                    caps.get(2)
+                        .or_else(|| caps.get(3))
+                        .or_else(|| caps.get(4))
+                        .map_or("", |m| m.as_str()),
+                );
+                if URL_ATTRIBUTES.contains(&name.as_str()) && url_policy.sanitize(&value).is_none()
+                {
+                    continue;
+                }
+                rslt.push_str(&format!(r#" {}="{}""#, name, escape_code_string(&value)));
+            }
+            rslt
+        }
+    }
+
+    /// Allowed tags which are open, from the outermost.
+    #[derive(Debug, Default)]
+    struct OpenTags {
+        names: Vec<String>,
+    }
+
+    impl OpenTags {
+        fn open(&mut self, name: String) {
+            if !VOID_TAGS.contains(&name.as_str()) {
+                self.names.push(name);
+            }
+        }
+
+        /// Tags to close for end tag of `name`, from the innermost,
+        /// which is empty when `name` is not open.
+        fn close(&mut self, name: &str) -> Vec<String> {
+            match self.names.iter().rposition(|open| open == name) {
+                Some(at) => self.names.drain(at..).rev().collect(),
+                None => vec![],
+            }
+        }
+
+        fn close_all(&mut self) -> Vec<String> {
+            self.names.drain(..).rev().collect()
+        }
+    }
+
+    /// Rest of `input` after the end tag of `name`, or empty when it is not closed.
+    fn skip_to_end_tag<'a>(input: &'a str, name: &str) -> &'a str {
+        let lower = input.to_ascii_lowercase();
+        let end_tag = format!("</{}", name);
+        match lower.find(&end_tag) {
+            Some(at) => match input[at..].find('>') {
+                Some(end) => &input[at + end + 1..],
+                None => "",
+            },
+            None => "",
+        }
+    }
+
+    /// Decode character references which browsers decode in attribute values.
+    ///
+    /// Unknown named references are kept, and they are escaped again on output.
+    fn decode_entities(input: &str) -> String {
+        ENTITY_PATTERN
+            .replace_all(input, |caps: &regex::Captures| {
+                let code = if let Some(hex) = caps.get(1) {
+                    u32::from_str_radix(hex.as_str(), 16).ok()
+                } else if let Some(dec) = caps.get(2) {
+                    dec.as_str().parse::<u32>().ok()
+                } else {
+                    match &caps[3] {
+                        "amp" => Some('&' as u32),
+                        "lt" => Some('<' as u32),
+                        "gt" => Some('>' as u32),
+                        "quot" => Some('"' as u32),
+                        "apos" => Some('\'' as u32),
+                        "colon" => Some(':' as u32),
+                        "Tab" => Some('\t' as u32),
+                        "NewLine" => Some('\n' as u32),
+                        _ => None,
+                    }
+                };
+                match code.and_then(char::from_u32) {
+                    Some(c) => c.to_string(),
+                    None => caps[0].to_string(),
+                }
+            })
+            .to_string()
+    }
+
+    #[cfg(test)]
+    mod test_sanitizer {
+        use super::*;
+
+        #[test]
+        fn test_sanitize() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: String,
+                expected: String,
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should keep allowed tags"),
+                    input: String::from("a <b>bold</b> and <br/>"),
+                    expected: String::from("a <b>bold</b> and <br>"),
+                },
+                TestCase {
+                    it: String::from("should drop script with its content"),
+                    input: String::from("a<script>alert('x')</script>b<SCRIPT>c"),
+                    expected: String::from("ab"),
+                },
+                TestCase {
+                    it: String::from("should drop unknown tags but keep content"),
+                    input: String::from("<blink>hoge</blink><!-- fuga -->"),
+                    expected: String::from("hoge"),
+                },
+                TestCase {
+                    it: String::from("should drop attributes not in allowlist"),
+                    input: String::from(
+                        r#"<img src=a.png onerror="alert(1)" style='x' alt="a&quot;b">"#,
+                    ),
+                    expected: String::from(r#"<img src="a.png" alt="a&quot;b">"#),
+                },
+                TestCase {
+                    it: String::from("should drop url rejected by url policy"),
+                    input: String::from(
+                        r#"<a href="java&#x73;cript&colon;alert(1)" title="t">x</a>"#,
+                    ),
+                    expected: String::from(r#"<a title="t">x</a>"#),
+                },
+                TestCase {
+                    it: String::from("should escape broken tags"),
+                    input: String::from(r#"1 < 2 > 0 <a href="x"#),
+                    expected: String::from(r#"1 &lt; 2 &gt; 0 &lt;a href="x"#),
+                },
+                TestCase {
+                    it: String::from("should close tags left open"),
+                    input: String::from("<div><b>a<br>b</div></i><em>c"),
+                    expected: String::from("<div><b>a<br>b</b></div><em>c</em>"),
+                },
+            ];
+            let sanitizer = HtmlSanitizer::default();
+            for test_case in test_cases.iter() {
+                let output = sanitizer.sanitize(&test_case.input, &UrlPolicy::default());
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+
+        #[test]
+        fn test_allowlist() {
+            let sanitizer = HtmlSanitizer {
+                allowed_tags: vec_string!["span"],
+                allowed_attributes: vec_string!["class"],
+            };
+            let output = sanitizer.sanitize(
+                r#"<span class="x" id="y"><b>z</b></span>"#,
+                &UrlPolicy::default(),
+            );
+            assert_eq!(output, r#"<span class="x">z</span>"#);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+pub mod source {
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+
+    /// Position in the source text.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Position {
+        /// 1-based line number
+        pub line: usize,
+        /// 1-based column counted in characters
+        pub column: usize,
+        /// 0-based byte offset
+        pub offset: usize,
+    }
+
+    impl Position {
+        pub fn new(line: usize, column: usize, offset: usize) -> Self {
+            Position {
+                line,
+                column,
+                offset,
+            }
+        }
+
+        /// Position right after `text`, which starts at this position.
+        pub fn advance(&self, text: &str) -> Self {
+            let mut position = *self;
+            for c in text.chars() {
+                if c == '\n' {
+                    position.line += 1;
+                    position.column = 1;
+                } else {
+                    position.column += 1;
+                }
+                position.offset += c.len_utf8();
+            }
+            position
+        }
+    }
+
+    /// Range in the source text. `end` is exclusive.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Span {
+        pub start: Position,
+        pub end: Position,
+    }
+
+    impl Span {
+        pub fn new(start: Position, end: Position) -> Self {
+            Span { start, end }
+        }
+    }
+
+    /// Line of the source text with its start position.
+    ///
+    /// Block parsers strip markers such as `> ` from lines, so `start` is not
+    /// always at the first column.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Line {
+        pub text: String,
+        pub start: Position,
+    }
+
+    impl Line {
+        pub fn new(text: String, start: Position) -> Self {
+            Line { text, start }
+        }
+
+        /// Position of the `byte`-th byte of the text.
+        pub fn position(&self, byte: usize) -> Position {
+            self.start.advance(&self.text[..byte])
+        }
+
+        pub fn end(&self) -> Position {
+            self.position(self.text.len())
+        }
+
+        pub fn span(&self) -> Span {
+            Span::new(self.start, self.end())
+        }
+
+        /// Rest of the line after the first `byte` bytes.
+        pub fn skip(&self, byte: usize) -> Self {
+            Line::new(self.text[byte..].to_string(), self.position(byte))
+        }
+
+        /// First `byte` bytes of the line.
+        pub fn take(&self, byte: usize) -> Self {
+            Line::new(self.text[..byte].to_string(), self.start)
+        }
+    }
+
+    /// Attach positions to lines which are split from one text by `\n`.
+    pub fn source_lines(text: Vec<String>) -> Vec<Line> {
+        let mut start = Position::new(1, 1, 0);
+        text.into_iter()
+            .map(|text| {
+                let line = Line::new(text, start);
+                start = Position::new(start.line + 1, 1, line.end().offset + 1);
+                line
+            })
+            .collect()
+    }
+
+    /// Span from the start of the first line to the end of the last line.
+    pub fn lines_span(lines: &[Line]) -> Span {
+        match (lines.first(), lines.last()) {
+            (Some(first), Some(last)) => Span::new(first.start, last.end()),
+            _ => Span::default(),
+        }
+    }
+
+    #[cfg(test)]
+    mod test_source {
+        use super::*;
+        use crate::vec_string;
+
+        #[test]
+        fn test_source_lines() {
+            let output = source_lines(vec_string!["# hello", "", "世界"]);
+            let expected = vec![
+                Line::new("# hello".to_string(), Position::new(1, 1, 0)),
+                Line::new("".to_string(), Position::new(2, 1, 8)),
+                Line::new("世界".to_string(), Position::new(3, 1, 9)),
+            ];
+            assert_eq!(output, expected);
+            assert_eq!(output[2].end(), Position::new(3, 3, 15));
+        }
+
+        #[test]
+        fn test_skip() {
+            let line = Line::new("> 引用 text".to_string(), Position::new(2, 1, 10));
+            let output = line.skip(9);
+            assert_eq!(
+                output,
+                Line::new("text".to_string(), Position::new(2, 6, 19))
+            );
+        }
+    }
+}
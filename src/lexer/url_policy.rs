@@ -0,0 +1,124 @@
+pub mod url_policy {
+    use crate::vec_string;
+    use once_cell::sync::Lazy;
+    use regex::Regex;
+
+    static SCHEME_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^([a-zA-Z][a-zA-Z0-9+.\-]*):").unwrap());
+
+    /// Policy applied to `href` of links and `src` of images.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct UrlPolicy {
+        /// Schemes allowed in urls, compared ignoring case.
+        ///
+        /// Relative urls, which have no scheme, are always allowed.
+        pub allowed_schemes: Vec<String>,
+        /// Add `rel="nofollow noopener"` to links to other sites.
+        pub rel_nofollow: bool,
+        /// `target` of links to other sites, such as `_blank`.
+        pub external_target: Option<String>,
+    }
+
+    impl Default for UrlPolicy {
+        fn default() -> Self {
+            UrlPolicy {
+                allowed_schemes: vec_string!["http", "https", "mailto"],
+                rel_nofollow: false,
+                external_target: None,
+            }
+        }
+    }
+
+    impl UrlPolicy {
+        /// Returns `url` if it is allowed, or `None` if it must not be emitted.
+        pub fn sanitize<'a>(&self, url: &'a str) -> Option<&'a str> {
+            // browsers ignore these characters while reading the scheme,
+            // so `java\tscript:` is still javascript
+            let normalized: String = url
+                .chars()
+                .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+                .collect();
+            match SCHEME_PATTERN.captures(&normalized) {
+                Some(caps) => {
+                    if self
+                        .allowed_schemes
+                        .iter()
+                        .any(|allowed| allowed.eq_ignore_ascii_case(&caps[1]))
+                    {
+                        Some(url)
+                    } else {
+                        None
+                    }
+                }
+                None => Some(url),
+            }
+        }
+
+        /// Whether `url` points to another site, that is, it is absolute http(s) url.
+        pub fn is_external(&self, url: &str) -> bool {
+            let url = url.trim_start().to_ascii_lowercase();
+            url.starts_with("http://") || url.starts_with("https://") || url.starts_with("//")
+        }
+    }
+
+    #[cfg(test)]
+    mod test_url_policy {
+        use super::*;
+
+        #[test]
+        fn test_sanitize() {
+            #[derive(Debug)]
+            struct TestCase {
+                it: String,
+                input: String,
+                expected: Option<String>,
+            }
+            let test_cases = [
+                TestCase {
+                    it: String::from("should allow https url"),
+                    input: String::from("https://example.com"),
+                    expected: Some(String::from("https://example.com")),
+                },
+                TestCase {
+                    it: String::from("should allow relative url"),
+                    input: String::from("../a:b.png"),
+                    expected: Some(String::from("../a:b.png")),
+                },
+                TestCase {
+                    it: String::from("should allow scheme in other case"),
+                    input: String::from("MAILTO:hoge@example.com"),
+                    expected: Some(String::from("MAILTO:hoge@example.com")),
+                },
+                TestCase {
+                    it: String::from("should reject javascript url"),
+                    input: String::from("javascript:alert(1)"),
+                    expected: None,
+                },
+                TestCase {
+                    it: String::from("should reject javascript url with whitespace"),
+                    input: String::from(" Java\tScript:alert(1)"),
+                    expected: None,
+                },
+                TestCase {
+                    it: String::from("should reject data url"),
+                    input: String::from("data:text/html;base64,PHNjcmlwdD4="),
+                    expected: None,
+                },
+            ];
+            let policy = UrlPolicy::default();
+            for test_case in test_cases.iter() {
+                let output = policy.sanitize(&test_case.input).map(|s| s.to_string());
+                assert_eq!(output, test_case.expected, "Failed: {}\n", test_case.it);
+            }
+        }
+
+        #[test]
+        fn test_is_external() {
+            let policy = UrlPolicy::default();
+            assert!(policy.is_external("https://example.com"));
+            assert!(policy.is_external("//example.com"));
+            assert!(!policy.is_external("/about"));
+            assert!(!policy.is_external("mailto:hoge@example.com"));
+        }
+    }
+}
@@ -0,0 +1,448 @@
+pub mod visitor {
+    use crate::lexer::builder::builder::MdNode;
+    use crate::lexer::lexer::lexer::{Content, ElementNode, InlineNode, Token};
+
+    /// Callbacks called while walking the syntax tree.
+    ///
+    /// `enter_*` is called before the children of the node are walked and
+    /// `leave_*` after them. Nodes without children only have `visit_*`.
+    #[allow(unused_variables)]
+    pub trait Visitor {
+        fn enter_heading(&mut self, node: &ElementNode) {}
+        fn leave_heading(&mut self, node: &ElementNode) {}
+        fn enter_paragraph(&mut self, node: &ElementNode) {}
+        fn leave_paragraph(&mut self, node: &ElementNode) {}
+        fn enter_list(&mut self, node: &ElementNode) {}
+        fn leave_list(&mut self, node: &ElementNode) {}
+        fn enter_list_item(&mut self, node: &ElementNode) {}
+        fn leave_list_item(&mut self, node: &ElementNode) {}
+        fn enter_blockquote(&mut self, node: &ElementNode) {}
+        fn leave_blockquote(&mut self, node: &ElementNode) {}
+        fn visit_code_block(&mut self, node: &ElementNode) {}
+        fn visit_table(&mut self, node: &ElementNode) {}
+        fn visit_thematic_break(&mut self, node: &ElementNode) {}
+        fn visit_html_block(&mut self, node: &ElementNode) {}
+
+        fn enter_emphasis(&mut self, node: &InlineNode) {}
+        fn leave_emphasis(&mut self, node: &InlineNode) {}
+        fn enter_strong(&mut self, node: &InlineNode) {}
+        fn leave_strong(&mut self, node: &InlineNode) {}
+        fn enter_link(&mut self, node: &InlineNode) {}
+        fn leave_link(&mut self, node: &InlineNode) {}
+        fn visit_text(&mut self, node: &InlineNode) {}
+        fn visit_code(&mut self, node: &InlineNode) {}
+        fn visit_image(&mut self, node: &InlineNode) {}
+        fn visit_line_break(&mut self, node: &InlineNode) {}
+        fn visit_html(&mut self, node: &InlineNode) {}
+    }
+
+    /// Same as [`Visitor`], but the nodes can be rewritten in place.
+    ///
+    /// Children are walked after `enter_*` returns, so they reflect its changes.
+    /// When `enter_*` changes the tag or kind of the node, `leave_*` is called for
+    /// the node as it is then, such as `leave_paragraph` for a heading turned into
+    /// a paragraph.
+    #[allow(unused_variables)]
+    pub trait VisitorMut {
+        fn enter_heading(&mut self, node: &mut ElementNode) {}
+        fn leave_heading(&mut self, node: &mut ElementNode) {}
+        fn enter_paragraph(&mut self, node: &mut ElementNode) {}
+        fn leave_paragraph(&mut self, node: &mut ElementNode) {}
+        fn enter_list(&mut self, node: &mut ElementNode) {}
+        fn leave_list(&mut self, node: &mut ElementNode) {}
+        fn enter_list_item(&mut self, node: &mut ElementNode) {}
+        fn leave_list_item(&mut self, node: &mut ElementNode) {}
+        fn enter_blockquote(&mut self, node: &mut ElementNode) {}
+        fn leave_blockquote(&mut self, node: &mut ElementNode) {}
+        fn visit_code_block(&mut self, node: &mut ElementNode) {}
+        fn visit_table(&mut self, node: &mut ElementNode) {}
+        fn visit_thematic_break(&mut self, node: &mut ElementNode) {}
+        fn visit_html_block(&mut self, node: &mut ElementNode) {}
+
+        fn enter_emphasis(&mut self, node: &mut InlineNode) {}
+        fn leave_emphasis(&mut self, node: &mut InlineNode) {}
+        fn enter_strong(&mut self, node: &mut InlineNode) {}
+        fn leave_strong(&mut self, node: &mut InlineNode) {}
+        fn enter_link(&mut self, node: &mut InlineNode) {}
+        fn leave_link(&mut self, node: &mut InlineNode) {}
+        fn visit_text(&mut self, node: &mut InlineNode) {}
+        fn visit_code(&mut self, node: &mut InlineNode) {}
+        fn visit_image(&mut self, node: &mut InlineNode) {}
+        fn visit_line_break(&mut self, node: &mut InlineNode) {}
+        fn visit_html(&mut self, node: &mut InlineNode) {}
+    }
+
+    pub fn walk_document<V: Visitor + ?Sized>(visitor: &mut V, document: &MdNode) {
+        for node in document.element_nodes() {
+            walk_element_node(visitor, node);
+        }
+    }
+
+    pub fn walk_element_node<V: Visitor + ?Sized>(visitor: &mut V, node: &ElementNode) {
+        let (tag, content, children) = match node {
+            ElementNode::Exist {
+                tag,
+                content,
+                children,
+                ..
+            } => (tag, content, children),
+            ElementNode::Nil => return,
+        };
+        match tag {
+            Token::H1 | Token::H2 | Token::H3 | Token::H4 | Token::H5 | Token::H6 => {
+                visitor.enter_heading(node)
+            }
+            Token::Ul | Token::Ol => visitor.enter_list(node),
+            Token::Li => visitor.enter_list_item(node),
+            Token::Blockquote => visitor.enter_blockquote(node),
+            Token::Code => visitor.visit_code_block(node),
+            Token::Table => visitor.visit_table(node),
+            Token::Hr => visitor.visit_thematic_break(node),
+            Token::Html => visitor.visit_html_block(node),
+            Token::P => visitor.enter_paragraph(node),
+        }
+        match &**content {
+            Content::ElementNodes { value }
+            | Content::List { value, .. }
+            | Content::ListItem { value, .. } => {
+                for child in value.iter() {
+                    walk_element_node(visitor, child);
+                }
+            }
+            Content::InlineNodes { value } => {
+                for child in value.iter() {
+                    walk_inline_node(visitor, child);
+                }
+            }
+            Content::Table { value } => {
+                let heads = value.head.iter().map(|head| &head.cell);
+                for child in heads.chain(value.body.iter().flatten()).flatten() {
+                    walk_inline_node(visitor, child);
+                }
+            }
+            Content::CodeBlock { .. } => {}
+        }
+        walk_element_node(visitor, children);
+        match tag {
+            Token::H1 | Token::H2 | Token::H3 | Token::H4 | Token::H5 | Token::H6 => {
+                visitor.leave_heading(node)
+            }
+            Token::Ul | Token::Ol => visitor.leave_list(node),
+            Token::Li => visitor.leave_list_item(node),
+            Token::Blockquote => visitor.leave_blockquote(node),
+            Token::Code | Token::Table | Token::Hr | Token::Html => {}
+            Token::P => visitor.leave_paragraph(node),
+        }
+    }
+
+    pub fn walk_inline_node<V: Visitor + ?Sized>(visitor: &mut V, node: &InlineNode) {
+        match node {
+            InlineNode::Text { .. } => visitor.visit_text(node),
+            InlineNode::Code { .. } => visitor.visit_code(node),
+            InlineNode::Image { .. } => visitor.visit_image(node),
+            InlineNode::SoftBreak { .. } | InlineNode::HardBreak { .. } => {
+                visitor.visit_line_break(node)
+            }
+            InlineNode::Html { .. } => visitor.visit_html(node),
+            InlineNode::Emphasis { children, .. } => {
+                visitor.enter_emphasis(node);
+                for child in children.iter() {
+                    walk_inline_node(visitor, child);
+                }
+                visitor.leave_emphasis(node);
+            }
+            InlineNode::Strong { children, .. } => {
+                visitor.enter_strong(node);
+                for child in children.iter() {
+                    walk_inline_node(visitor, child);
+                }
+                visitor.leave_strong(node);
+            }
+            InlineNode::Link { children, .. } => {
+                visitor.enter_link(node);
+                for child in children.iter() {
+                    walk_inline_node(visitor, child);
+                }
+                visitor.leave_link(node);
+            }
+        }
+    }
+
+    pub fn walk_document_mut<V: VisitorMut + ?Sized>(visitor: &mut V, document: &mut MdNode) {
+        for node in document.element_nodes_mut() {
+            walk_element_node_mut(visitor, node);
+        }
+    }
+
+    pub fn walk_element_node_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut ElementNode) {
+        let tag = match node {
+            ElementNode::Exist { tag, .. } => *tag,
+            ElementNode::Nil => return,
+        };
+        match tag {
+            Token::H1 | Token::H2 | Token::H3 | Token::H4 | Token::H5 | Token::H6 => {
+                visitor.enter_heading(node)
+            }
+            Token::Ul | Token::Ol => visitor.enter_list(node),
+            Token::Li => visitor.enter_list_item(node),
+            Token::Blockquote => visitor.enter_blockquote(node),
+            Token::Code => visitor.visit_code_block(node),
+            Token::Table => visitor.visit_table(node),
+            Token::Hr => visitor.visit_thematic_break(node),
+            Token::Html => visitor.visit_html_block(node),
+            Token::P => visitor.enter_paragraph(node),
+        }
+        if let ElementNode::Exist {
+            content, children, ..
+        } = node
+        {
+            match &mut **content {
+                Content::ElementNodes { value }
+                | Content::List { value, .. }
+                | Content::ListItem { value, .. } => {
+                    for child in value.iter_mut() {
+                        walk_element_node_mut(visitor, child);
+                    }
+                }
+                Content::InlineNodes { value } => {
+                    for child in value.iter_mut() {
+                        walk_inline_node_mut(visitor, child);
+                    }
+                }
+                Content::Table { value } => {
+                    let heads = value.head.iter_mut().map(|head| &mut head.cell);
+                    for child in heads.chain(value.body.iter_mut().flatten()).flatten() {
+                        walk_inline_node_mut(visitor, child);
+                    }
+                }
+                Content::CodeBlock { .. } => {}
+            }
+            walk_element_node_mut(visitor, children);
+        }
+        // `enter_*` may have changed the tag, so `leave_*` follows the node as it is now
+        let tag = match node {
+            ElementNode::Exist { tag, .. } => *tag,
+            ElementNode::Nil => return,
+        };
+        match tag {
+            Token::H1 | Token::H2 | Token::H3 | Token::H4 | Token::H5 | Token::H6 => {
+                visitor.leave_heading(node)
+            }
+            Token::Ul | Token::Ol => visitor.leave_list(node),
+            Token::Li => visitor.leave_list_item(node),
+            Token::Blockquote => visitor.leave_blockquote(node),
+            Token::Code | Token::Table | Token::Hr | Token::Html => {}
+            Token::P => visitor.leave_paragraph(node),
+        }
+    }
+
+    pub fn walk_inline_node_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut InlineNode) {
+        let has_children = matches!(
+            node,
+            InlineNode::Emphasis { .. } | InlineNode::Strong { .. } | InlineNode::Link { .. }
+        );
+        match node {
+            InlineNode::Text { .. } => visitor.visit_text(node),
+            InlineNode::Code { .. } => visitor.visit_code(node),
+            InlineNode::Image { .. } => visitor.visit_image(node),
+            InlineNode::SoftBreak { .. } | InlineNode::HardBreak { .. } => {
+                visitor.visit_line_break(node)
+            }
+            InlineNode::Html { .. } => visitor.visit_html(node),
+            InlineNode::Emphasis { .. } => visitor.enter_emphasis(node),
+            InlineNode::Strong { .. } => visitor.enter_strong(node),
+            InlineNode::Link { .. } => visitor.enter_link(node),
+        }
+        if !has_children {
+            return;
+        }
+        walk_inline_children_mut(visitor, node);
+        match node {
+            InlineNode::Emphasis { .. } => visitor.leave_emphasis(node),
+            InlineNode::Strong { .. } => visitor.leave_strong(node),
+            InlineNode::Link { .. } => visitor.leave_link(node),
+            _ => {}
+        }
+    }
+
+    fn walk_inline_children_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut InlineNode) {
+        if let InlineNode::Emphasis { children, .. }
+        | InlineNode::Strong { children, .. }
+        | InlineNode::Link { children, .. } = node
+        {
+            for child in children.iter_mut() {
+                walk_inline_node_mut(visitor, child);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod test_visitor {
+        use super::*;
+        use crate::lexer::lexer::lexer::Lexer;
+        use crate::vec_string;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_collect_links() {
+            struct LinkCollector {
+                links: Vec<String>,
+            }
+
+            impl Visitor for LinkCollector {
+                fn enter_link(&mut self, node: &InlineNode) {
+                    if let InlineNode::Link { href, .. } = node {
+                        self.links.push(href.to_string());
+                    }
+                }
+            }
+
+            let document = Lexer::new(vec_string![
+                "# [hoge](https://example.com/1)",
+                "* aaa",
+                "  * *[fuga](https://example.com/2)*",
+                "> [piyo](https://example.com/3)",
+            ])
+            .parse();
+            let mut collector = LinkCollector { links: vec![] };
+            walk_document(&mut collector, &document);
+            let expected: Vec<String> = vec_string![
+                "https://example.com/1",
+                "https://example.com/2",
+                "https://example.com/3",
+            ];
+            assert_eq!(collector.links, expected);
+        }
+
+        #[test]
+        fn test_enter_and_leave_order() {
+            struct Recorder {
+                events: Vec<String>,
+            }
+
+            impl Visitor for Recorder {
+                fn enter_list(&mut self, _node: &ElementNode) {
+                    self.events.push("enter list".to_string());
+                }
+                fn leave_list(&mut self, _node: &ElementNode) {
+                    self.events.push("leave list".to_string());
+                }
+                fn enter_list_item(&mut self, _node: &ElementNode) {
+                    self.events.push("enter item".to_string());
+                }
+                fn leave_list_item(&mut self, _node: &ElementNode) {
+                    self.events.push("leave item".to_string());
+                }
+                fn visit_text(&mut self, node: &InlineNode) {
+                    if let InlineNode::Text { value, .. } = node {
+                        self.events.push(value.to_string());
+                    }
+                }
+            }
+
+            let document = Lexer::new(vec_string!["* hoge", "  * fuga"]).parse();
+            let mut recorder = Recorder { events: vec![] };
+            walk_document(&mut recorder, &document);
+            let expected: Vec<String> = vec_string![
+                "enter list",
+                "enter item",
+                "hoge",
+                "enter list",
+                "enter item",
+                "fuga",
+                "leave item",
+                "leave list",
+                "leave item",
+                "leave list",
+            ];
+            assert_eq!(recorder.events, expected);
+        }
+
+        #[test]
+        fn test_leave_changed_node() {
+            struct Flattener {
+                events: Vec<String>,
+            }
+
+            impl VisitorMut for Flattener {
+                fn enter_heading(&mut self, node: &mut ElementNode) {
+                    self.events.push("enter heading".to_string());
+                    if let ElementNode::Exist { tag, .. } = node {
+                        *tag = Token::P;
+                    }
+                }
+                fn leave_heading(&mut self, _node: &mut ElementNode) {
+                    self.events.push("leave heading".to_string());
+                }
+                fn leave_paragraph(&mut self, _node: &mut ElementNode) {
+                    self.events.push("leave paragraph".to_string());
+                }
+                fn enter_emphasis(&mut self, node: &mut InlineNode) {
+                    self.events.push("enter emphasis".to_string());
+                    if let InlineNode::Emphasis { children, span } = node {
+                        *node = InlineNode::Strong {
+                            children: children.clone(),
+                            span: *span,
+                        };
+                    }
+                }
+                fn leave_strong(&mut self, _node: &mut InlineNode) {
+                    self.events.push("leave strong".to_string());
+                }
+            }
+
+            let mut document = Lexer::new(vec_string!["# *hoge*"]).parse();
+            let mut flattener = Flattener { events: vec![] };
+            walk_document_mut(&mut flattener, &mut document);
+            let expected: Vec<String> = vec_string![
+                "enter heading",
+                "enter emphasis",
+                "leave strong",
+                "leave paragraph",
+            ];
+            assert_eq!(flattener.events, expected);
+            assert_eq!(
+                document.to_html_string(),
+                r#"<p class="flav-md-text flav-md-p"><strong class="flav-md-strong">hoge</strong></p>"#
+            );
+        }
+
+        #[test]
+        fn test_rewrite_document() {
+            struct Rewriter;
+
+            impl VisitorMut for Rewriter {
+                fn enter_heading(&mut self, node: &mut ElementNode) {
+                    if let ElementNode::Exist { tag, .. } = node {
+                        *tag = match tag {
+                            Token::H1 => Token::H2,
+                            Token::H2 => Token::H3,
+                            Token::H3 => Token::H4,
+                            Token::H4 => Token::H5,
+                            _ => Token::H6,
+                        };
+                    }
+                }
+
+                fn visit_image(&mut self, node: &mut InlineNode) {
+                    if let InlineNode::Image { src, .. } = node {
+                        if !src.contains("://") {
+                            *src = format!("https://example.com/{}", src);
+                        }
+                    }
+                }
+            }
+
+            let mut document =
+                Lexer::new(vec_string!["# hoge", "## fuga", "![piyo](a.png)"]).parse();
+            walk_document_mut(&mut Rewriter, &mut document);
+            let expected = Lexer::new(vec_string![
+                "## hoge",
+                "### fuga",
+                "![piyo](https://example.com/a.png)"
+            ])
+            .parse();
+            assert_eq!(document.to_html_string(), expected.to_html_string());
+        }
+    }
+}
@@ -2,13 +2,25 @@
 //!
 //! You can parse markdown docs to styled html.
 //!
+#![allow(clippy::module_inception)]
 use crate::lexer::lexer::lexer::Lexer;
+use crate::util::string::string::split_string;
 extern crate wasm_bindgen;
 use wasm_bindgen::prelude::*;
 
+pub mod ast;
 mod lexer;
 mod util;
 
+use crate::ast::Document;
+pub use crate::lexer::code_handler::code_handler::{
+    CodeBlockHandler, CodeBlockHandlers, CodeBlockOutput,
+};
+pub use crate::lexer::error::error::ParseError;
+pub use crate::lexer::renderer::renderer::{FlavHtmlRenderer, HtmlTag, RenderOptions, Renderer};
+pub use crate::lexer::sanitizer::sanitizer::HtmlSanitizer;
+pub use crate::lexer::url_policy::url_policy::UrlPolicy;
+
 #[cfg(feature = "wee_alloc")]
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
@@ -22,6 +34,10 @@ pub struct FlavMd {
     ///
     /// This param is  passed by [`FlavMd::build`]
     css_text: String,
+    /// Errors recovered in the last [`FlavMd::build`]
+    diagnostics: Vec<ParseError>,
+    /// Options used in [`FlavMd::build`] and [`FlavMd::try_build`]
+    render_options: RenderOptions,
 }
 
 /// impl for flav md engine
@@ -35,16 +51,167 @@ impl FlavMd {
         FlavMd {
             html_text: "".to_string(),
             css_text: "".to_string(),
+            diagnostics: vec![],
+            render_options: RenderOptions::default(),
         }
     }
 
+    /// Emit `data-source-line` on each block element,
+    /// and `data-source-line-end` too when `with_end` is true.
+    ///
+    /// This is useful to sync scroll between markdown editor and its preview.
+    #[wasm_bindgen(js_name = enableSourceLine)]
+    pub fn enable_source_line(&mut self, with_end: bool) {
+        self.render_options.source_line = true;
+        self.render_options.source_line_end = with_end;
+    }
+
+    /// Use `class_prefix` instead of `flav-md` in class names, such as `docs-md-h1`.
+    #[wasm_bindgen(js_name = setClassPrefix)]
+    pub fn set_class_prefix(&mut self, class_prefix: String) {
+        self.render_options.class_prefix = class_prefix;
+    }
+
+    /// Emit html in the markdown instead of escaping it.
+    ///
+    /// Tags and attributes not in the allowlist are dropped.
+    #[wasm_bindgen(js_name = enableRawHtml)]
+    pub fn enable_raw_html(&mut self) {
+        self.render_options.allow_raw_html = true;
+    }
+
+    /// Emit checkboxes of task list items enabled, with `data-source-line` of their items.
+    ///
+    /// By default, they are disabled.
+    #[wasm_bindgen(js_name = enableTaskCheckbox)]
+    pub fn enable_task_checkbox(&mut self) {
+        self.render_options.task_checkbox_enabled = true;
+    }
+
+    /// Replace the allowlist of tags and attributes kept in raw html.
+    #[wasm_bindgen(js_name = setHtmlAllowlist)]
+    pub fn set_html_allowlist(&mut self, tags: Vec<String>, attributes: Vec<String>) {
+        self.render_options.html_sanitizer = Some(HtmlSanitizer {
+            allowed_tags: tags,
+            allowed_attributes: attributes,
+        });
+    }
+
+    /// Set attributes of links to other sites.
+    ///
+    /// With `rel_nofollow`, they have `rel="nofollow noopener"`, and `target` is set as it is.
+    #[wasm_bindgen(js_name = setExternalLink)]
+    pub fn set_external_link(&mut self, rel_nofollow: bool, target: Option<String>) {
+        self.render_options.url_policy.rel_nofollow = rel_nofollow;
+        self.render_options.url_policy.external_target = target;
+    }
+
     /// Build html text with given markdown and css.
+    ///
+    /// Malformed markdown is rendered as far as possible.
+    /// Use [`FlavMd::try_build`] to reject it.
     pub fn build(&mut self, md_text: String, css_text: String) -> String {
-        let lexer = Lexer::new(md_text.split("\n").map(|s| s.to_string()).collect());
-        self.html_text = lexer.parse().to_html_string();
+        let md_node = parse(&md_text);
+        self.html_text = md_node.to_html_string_with(&self.render_options);
+        self.diagnostics = md_node.diagnostics().to_vec();
         self.css_text = css_text;
         format!("<style>{}</style>\n{}", self.css_text, self.html_text)
     }
+
+    /// Build html text with given markdown and css,
+    /// returning [`ParseError`] when the markdown is malformed.
+    ///
+    /// In WebAssembly, the error is thrown as a string.
+    #[wasm_bindgen(js_name = tryBuild)]
+    pub fn try_build(&mut self, md_text: String, css_text: String) -> Result<String, ParseError> {
+        self.html_text = try_parse(&md_text)?.to_html_string_with(&self.render_options);
+        self.diagnostics = vec![];
+        self.css_text = css_text;
+        Ok(format!(
+            "<style>{}</style>\n{}",
+            self.css_text, self.html_text
+        ))
+    }
+
+    /// Build json text of the syntax tree with given markdown.
+    ///
+    /// The json is serialized [`ast::Document`], so it can be rendered by your own components.
+    #[cfg(feature = "serde")]
+    #[wasm_bindgen(js_name = buildJson)]
+    pub fn build_json(&self, md_text: String) -> String {
+        serde_json::to_string(&parse(&md_text)).expect("document is always serializable")
+    }
+}
+
+impl FlavMd {
+    /// Set options to render html.
+    pub fn with_render_options(self, render_options: RenderOptions) -> Self {
+        FlavMd {
+            render_options,
+            ..self
+        }
+    }
+
+    /// Render fenced code blocks of `language` with `handler`,
+    /// such as `mermaid` diagrams or `csv` tables.
+    ///
+    /// `handler` takes the code and the rest of the info string,
+    /// and returns `None` to render the code block as usual.
+    pub fn with_code_block_handler<F>(mut self, language: &str, handler: F) -> Self
+    where
+        F: Fn(&str, Option<&str>) -> Option<CodeBlockOutput> + Send + Sync + 'static,
+    {
+        self.render_options
+            .code_block_handlers
+            .insert(language, handler);
+        self
+    }
+
+    /// Errors recovered while building the last document.
+    pub fn diagnostics(&self) -> &[ParseError] {
+        &self.diagnostics
+    }
+}
+
+impl Default for FlavMd {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse markdown text to [`Document`].
+///
+/// This never fails. Malformed markdown is recovered and reported
+/// by [`Document::diagnostics`].
+///
+/// # example
+///
+/// ```
+/// use flav_md_engine::ast::{Content, ElementNode, InlineNode, Position, Span, Token};
+///
+/// let document = flav_md_engine::parse("# sample");
+/// assert_eq!(
+///     document.element_nodes(),
+///     &[ElementNode::new(
+///         Token::H1,
+///         Content::InlineNodes {
+///             value: vec![InlineNode::Text {
+///                 value: "sample".to_string(),
+///                 span: Span::new(Position::new(1, 3, 2), Position::new(1, 9, 8)),
+///             }]
+///         },
+///         Box::new(ElementNode::Nil),
+///     )
+///     .with_span(Span::new(Position::new(1, 1, 0), Position::new(1, 9, 8)))]
+/// );
+/// ```
+pub fn parse(md_text: &str) -> Document {
+    Lexer::new(split_string(md_text.to_string())).parse()
+}
+
+/// Parse markdown text to [`Document`], returning [`ParseError`] when the markdown is malformed.
+pub fn try_parse(md_text: &str) -> Result<Document, ParseError> {
+    Lexer::new(split_string(md_text.to_string())).try_parse()
 }
 
 /// Generate flav md instance
@@ -72,7 +239,7 @@ pub fn create_flav_md() -> FlavMd {
 
 #[cfg(test)]
 mod test {
-    use crate::create_flav_md;
+    use crate::{create_flav_md, CodeBlockOutput, ParseError};
 
     #[test]
     fn correctly_build_file() {
@@ -85,4 +252,98 @@ mod test {
         let actual = create_flav_md().build("# sample".to_string(), css_text);
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn return_error_when_code_block_is_not_terminated() {
+        let md_text = "# sample\n```rust\nfn main() {}".to_string();
+        let actual = create_flav_md().try_build(md_text, "".to_string());
+        assert_eq!(
+            actual,
+            Err(ParseError::UnterminatedCodeBlock { line: 2, column: 1 })
+        );
+    }
+
+    #[test]
+    fn keep_diagnostics_when_code_block_is_not_terminated() {
+        let md_text = "# sample\n```text\nfn main() {}".to_string();
+        let mut flav_md = create_flav_md();
+        let actual = flav_md.build(md_text, "".to_string());
+        assert_eq!(
+            actual,
+            "<style></style>\n<h1 class=\"flav-md-text flav-md-h1 flav-md-h\">sample</h1>\n<pre><code class=\"flav-md-code language-text\">fn main() {}\n</code></pre>"
+        );
+        assert_eq!(
+            flav_md.diagnostics(),
+            &[ParseError::UnterminatedCodeBlock { line: 2, column: 1 }]
+        );
+    }
+
+    #[test]
+    fn build_table_with_extra_cells() {
+        let md_text = "|a|\n|-|\n|1|2|".to_string();
+        let mut flav_md = create_flav_md();
+        let actual = flav_md.build(md_text.clone(), "".to_string());
+        assert_eq!(
+            actual,
+            "<style></style>\n<table>\n  <thead>\n    <tr>\n      <th style=\"text-align: left\">a</th>\n    </tr>\n  </thead>\n  <tbody>\n    <tr>\n      <td style=\"text-align: left\">1</td>\n    </tr>\n  </tbody>\n</table>"
+        );
+        assert_eq!(
+            flav_md.diagnostics(),
+            &[ParseError::MalformedTable { line: 3, column: 4 }]
+        );
+        assert_eq!(
+            create_flav_md().try_build(md_text, "".to_string()),
+            Err(ParseError::MalformedTable { line: 3, column: 4 })
+        );
+    }
+
+    #[test]
+    fn build_with_source_line() {
+        let mut flav_md = create_flav_md();
+        flav_md.enable_source_line(true);
+        let actual = flav_md.build("# sample\n\n* hoge".to_string(), "".to_string());
+        assert_eq!(
+            actual,
+            "<style></style>\n<h1 class=\"flav-md-text flav-md-h1 flav-md-h\" data-source-line=\"1\" data-source-line-end=\"1\">sample</h1>\n<ul class=\"flav-md-ul\" data-source-line=\"3\" data-source-line-end=\"3\">\n  <li class=\"flav-md-text flav-md-li\" data-source-line=\"3\" data-source-line-end=\"3\">hoge</li>\n</ul>"
+        );
+    }
+
+    #[test]
+    fn build_with_code_block_handler() {
+        let mut flav_md = create_flav_md()
+            .with_code_block_handler("mermaid", |code, _| {
+                Some(CodeBlockOutput::Html(format!(
+                    "<div class=\"mermaid\">{}</div>",
+                    code
+                )))
+            })
+            .with_code_block_handler("csv", |code, _| {
+                let rows: Vec<String> = code
+                    .lines()
+                    .map(|line| format!("|{}|", line.replace(',', "|")))
+                    .collect();
+                let md_text = format!("{}\n|-|-|\n{}", rows[0], rows[1..].join("\n"));
+                Some(CodeBlockOutput::ElementNodes(
+                    crate::parse(&md_text).element_nodes().to_vec(),
+                ))
+            });
+        let actual = flav_md.build(
+            "```mermaid\ngraph TD\n```\n```csv\na,b\n1,2\n```\n```text\nhoge\n```".to_string(),
+            "".to_string(),
+        );
+        assert_eq!(
+            actual,
+            "<style></style>\n<div class=\"mermaid\">graph TD</div>\n<table>\n  <thead>\n    <tr>\n      <th style=\"text-align: left\">a</th>\n      <th style=\"text-align: left\">b</th>\n    </tr>\n  </thead>\n  <tbody>\n    <tr>\n      <td style=\"text-align: left\">1</td>\n      <td style=\"text-align: left\">2</td>\n    </tr>\n  </tbody>\n</table>\n<pre><code class=\"flav-md-code language-text\">hoge\n</code></pre>"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn correctly_build_json() {
+        let actual = create_flav_md().build_json("# sample".to_string());
+        let expected = r#"{"element_nodes":[{"Exist":{"tag":"H1","content":{"InlineNodes":{"value":[{"Text":{"value":"sample","span":{"start":{"line":1,"column":3,"offset":2},"end":{"line":1,"column":9,"offset":8}}}}]}},"children":"Nil","span":{"start":{"line":1,"column":1,"offset":0},"end":{"line":1,"column":9,"offset":8}}}}],"diagnostics":[]}"#;
+        assert_eq!(actual, expected);
+        let document: crate::ast::Document = serde_json::from_str(&actual).unwrap();
+        assert_eq!(document, crate::parse("# sample"));
+    }
 }
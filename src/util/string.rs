@@ -1,9 +1,9 @@
 pub mod string {
     pub fn split_string(content: String) -> Vec<String> {
-        content.split('\n').into_iter().map(|s| s.into()).collect()
+        content.split('\n').map(|s| s.into()).collect()
     }
 
-    pub fn escape_code_string(content: &String) -> String {
+    pub fn escape_code_string(content: &str) -> String {
         content
             .replace("&", "&amp;")
             .replace("<", "&lt;")